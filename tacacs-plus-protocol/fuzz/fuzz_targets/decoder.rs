@@ -0,0 +1,59 @@
+//! Ensures that the packet decoder splits arbitrarily chunked input into the same frames as a one-shot decode.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use tacacs_plus_protocol::PacketDecoder;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    max_body_length: u16,
+    chunk_sizes: Vec<u8>,
+    stream: &'a [u8],
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let expected = decode(&input, &[input.stream.len()]);
+
+    let chunk_sizes: Vec<usize> = input
+        .chunk_sizes
+        .iter()
+        .map(|&size| usize::from(size))
+        .collect();
+    assert_eq!(decode(&input, &chunk_sizes), expected);
+});
+
+/// Feeds the input stream to a decoder in chunks of the provided sizes, returning the decoded frames
+/// and whether an error was encountered.
+fn decode(input: &Input<'_>, chunk_sizes: &[usize]) -> (Vec<Vec<u8>>, bool) {
+    let mut decoder = PacketDecoder::with_max_body_length(Vec::new(), input.max_body_length.into());
+    let mut frames = Vec::new();
+
+    let mut remaining = input.stream;
+    let mut chunk_sizes = chunk_sizes.iter().cycle();
+
+    while !remaining.is_empty() {
+        // empty chunks would never make progress, and no sizes means feeding everything at once
+        let chunk_size = chunk_sizes
+            .next()
+            .map_or(remaining.len(), |&size| size.max(1));
+        let (mut chunk, rest) = remaining.split_at(chunk_size.min(remaining.len()));
+        remaining = rest;
+
+        while !chunk.is_empty() {
+            let consumed = match decoder.feed(chunk) {
+                Ok(consumed) => consumed,
+                Err(_) => return (frames, true),
+            };
+            chunk = &chunk[consumed..];
+
+            if let Some(frame) = decoder.take_frame() {
+                frames.push(frame.to_vec());
+            }
+        }
+    }
+
+    (frames, false)
+}
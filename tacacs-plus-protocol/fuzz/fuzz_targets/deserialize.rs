@@ -0,0 +1,53 @@
+//! Ensures that deserializing arbitrary (potentially malicious) bytes never panics.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use tacacs_plus_protocol::{accounting, authentication, authorization};
+use tacacs_plus_protocol::{AnyPacket, Deserialize, HeaderInfo, Packet, PacketBody, PacketKind};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = HeaderInfo::try_from(data);
+
+    deserialize_as::<authentication::Start>(data);
+    deserialize_as::<authentication::Continue>(data);
+    deserialize_as::<authentication::Reply>(data);
+    deserialize_as::<authorization::Request>(data);
+    deserialize_as::<authorization::Reply>(data);
+    deserialize_as::<accounting::Request>(data);
+    deserialize_as::<accounting::Reply>(data);
+
+    // body length extraction is also done on untrusted input
+    let _ = authentication::Continue::extract_total_length(data);
+    let _ = authentication::Reply::extract_total_length(data);
+    let _ = authorization::Request::extract_total_length(data);
+    let _ = authorization::Reply::extract_total_length(data);
+    let _ = accounting::Request::extract_total_length(data);
+    let _ = accounting::Reply::extract_total_length(data);
+
+    if let Ok(packet) = AnyPacket::deserialize_unobfuscated(data) {
+        exercise_any_packet(&packet);
+    }
+
+    let mut obfuscated = data.to_vec();
+    if let Ok(packet) = AnyPacket::deserialize(b"fuzzing key", &mut obfuscated) {
+        exercise_any_packet(&packet);
+    }
+});
+
+fn deserialize_as<'raw, B: PacketBody + Deserialize<'raw>>(data: &'raw [u8]) {
+    let _ = Packet::<B>::deserialize_unobfuscated(data);
+}
+
+/// Iterates over any arguments in a deserialized packet, since they're decoded lazily.
+fn exercise_any_packet(packet: &AnyPacket<'_>) {
+    match packet.body() {
+        PacketKind::AuthorizationRequest(request) => request.iter_arguments().for_each(drop),
+        PacketKind::AuthorizationReply(reply) => reply.iter_arguments().for_each(drop),
+        PacketKind::AccountingRequest(request) => request.iter_arguments().for_each(drop),
+        _ => {}
+    }
+
+    let _ = packet.to_owned();
+}
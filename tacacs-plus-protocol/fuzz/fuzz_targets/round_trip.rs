@@ -0,0 +1,76 @@
+//! Ensures that serializing a valid packet and deserializing it again produces an equivalent packet.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use tacacs_plus_protocol::{accounting, authentication, authorization};
+use tacacs_plus_protocol::{Packet, PacketBody, Serialize};
+
+#[derive(Debug, Arbitrary)]
+enum AnyBody<'a> {
+    AuthenticationStart(Packet<authentication::Start<'a>>),
+    AuthenticationContinue(Packet<authentication::Continue<'a>>),
+    AuthenticationReply(Packet<authentication::Reply<'a>>),
+    AuthorizationRequest(Packet<authorization::Request<'a>>),
+    AuthorizationReply(Packet<authorization::Reply<'a>>),
+    AccountingRequest(Packet<accounting::Request<'a>>),
+    AccountingReply(Packet<accounting::Reply<'a>>),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    key: Option<&'a [u8]>,
+    packet: AnyBody<'a>,
+}
+
+/// Serializes a packet, deserializes it as the same body type and checks that it reserializes identically.
+///
+/// Some fields are normalized during deserialization (e.g., empty fields becoming absent), so the
+/// serialized bytes are compared instead of the packets themselves.
+macro_rules! round_trip {
+    ($key:expr, $packet:expr, $body:ty) => {{
+        let key: Option<&[u8]> = $key;
+        let serialized = serialize(key, $packet);
+
+        // deserialization is done on a copy, since deobfuscation is done in place
+        let mut buffer = serialized.clone();
+        let deserialized: Packet<$body> = match key {
+            Some(key) => Packet::deserialize(key, &mut buffer),
+            None => Packet::deserialize_unobfuscated(&buffer),
+        }
+        .expect("serialized packet should deserialize");
+
+        assert_eq!(serialize(key, deserialized), serialized);
+    }};
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let key = input.key;
+
+    match input.packet {
+        AnyBody::AuthenticationStart(packet) => round_trip!(key, packet, authentication::Start),
+        AnyBody::AuthenticationContinue(packet) => {
+            round_trip!(key, packet, authentication::Continue)
+        }
+        AnyBody::AuthenticationReply(packet) => round_trip!(key, packet, authentication::Reply),
+        AnyBody::AuthorizationRequest(packet) => round_trip!(key, packet, authorization::Request),
+        AnyBody::AuthorizationReply(packet) => round_trip!(key, packet, authorization::Reply),
+        AnyBody::AccountingRequest(packet) => round_trip!(key, packet, accounting::Request),
+        AnyBody::AccountingReply(packet) => round_trip!(key, packet, accounting::Reply),
+    }
+});
+
+fn serialize<B: PacketBody + Serialize>(key: Option<&[u8]>, packet: Packet<B>) -> Vec<u8> {
+    let mut buffer = vec![0; packet.wire_size()];
+
+    let length = match key {
+        Some(key) => packet.serialize(key, &mut buffer),
+        None => packet.serialize_unobfuscated(&mut buffer),
+    }
+    .expect("valid packet should serialize");
+
+    assert_eq!(length, buffer.len());
+    buffer
+}
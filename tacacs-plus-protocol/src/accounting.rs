@@ -3,14 +3,15 @@
 use bitflags::bitflags;
 use byteorder::{ByteOrder, NetworkEndian};
 use core::fmt;
-use getset::Getters;
+use getset::{CopyGetters, Getters};
 use num_enum::{TryFromPrimitive, TryFromPrimitiveError};
 
 use super::{
     Arguments, AuthenticationContext, AuthenticationMethod, Deserialize, DeserializeError,
     PacketBody, PacketType, Serialize, SerializeError, UserInformation,
 };
-use crate::FieldText;
+use crate::arguments::{ArgumentsInfo, PacketArguments};
+use crate::{ArgumentsIterator, FieldText};
 
 #[cfg(test)]
 mod tests;
@@ -18,11 +19,15 @@ mod tests;
 #[cfg(feature = "std")]
 mod owned;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+
 #[cfg(feature = "std")]
-pub use owned::ReplyOwned;
+pub use owned::{ReplyOwned, RequestOwned};
 
 bitflags! {
     /// Raw bitflags for accounting request packet.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy)]
     struct RawFlags: u8 {
         const START    = 0b00000010;
         const STOP     = 0b00000100;
@@ -32,6 +37,7 @@ bitflags! {
 
 /// Valid flag combinations for a TACACS+ account REQUEST packet.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Flags {
     /// Start of a task.
     StartRecord,
@@ -72,28 +78,59 @@ impl From<Flags> for RawFlags {
     }
 }
 
+impl TryFrom<RawFlags> for Flags {
+    type Error = DeserializeError;
+
+    fn try_from(value: RawFlags) -> Result<Self, Self::Error> {
+        // valid combinations are listed in RFC8907 section 7.2: https://www.rfc-editor.org/rfc/rfc8907.html#section-7.2-10
+        if value == RawFlags::START {
+            Ok(Flags::StartRecord)
+        } else if value == RawFlags::STOP {
+            Ok(Flags::StopRecord)
+        } else if value == RawFlags::WATCHDOG {
+            Ok(Flags::WatchdogNoUpdate)
+        } else if value == RawFlags::WATCHDOG | RawFlags::START {
+            Ok(Flags::WatchdogUpdate)
+        } else {
+            // anything else (e.g., START | STOP, or no flags at all) is invalid
+            Err(DeserializeError::InvalidBodyFlags(value.bits()))
+        }
+    }
+}
+
 impl Flags {
     /// The number of bytes occupied by a flag set on the wire.
     pub(super) const WIRE_SIZE: usize = 1;
+
+    /// Converts a raw flag byte from a request packet body to a valid flag combination.
+    fn deserialize(flag_byte: u8) -> Result<Self, DeserializeError> {
+        RawFlags::from_bits(flag_byte)
+            .ok_or(DeserializeError::InvalidBodyFlags(flag_byte))?
+            .try_into()
+    }
 }
 
 /// An accounting request packet, used to start, stop, or provide progress on a running job.
-#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Getters, CopyGetters)]
 pub struct Request<'packet> {
     /// Flags to indicate what kind of accounting record this packet includes.
+    #[getset(get_copy = "pub")]
     flags: Flags,
 
     /// Method used to authenticate to TACACS+ client.
+    #[getset(get_copy = "pub")]
     authentication_method: AuthenticationMethod,
 
     /// Other information about authentication to TACACS+ client.
+    #[getset(get_copy = "pub")]
     authentication: AuthenticationContext,
 
     /// Information about the user connected to the client.
+    #[getset(get = "pub")]
     user_information: UserInformation<'packet>,
 
     /// Arguments to provide additional information to the server.
-    arguments: Arguments<'packet>,
+    arguments: PacketArguments<'packet>,
 }
 
 impl<'packet> Request<'packet> {
@@ -113,7 +150,44 @@ impl<'packet> Request<'packet> {
             authentication_method,
             authentication,
             user_information,
-            arguments,
+            arguments: PacketArguments::Provided(arguments),
+        }
+    }
+
+    /// Returns an iterator over the arguments included in this request packet.
+    ///
+    /// Arguments of a deserialized packet are decoded lazily as the iterator is advanced.
+    pub fn iter_arguments(&self) -> ArgumentsIterator<'_> {
+        self.arguments.iter()
+    }
+
+    /// Determines the length of a request packet based on encoded lengths at the beginning of the packet body, if possible.
+    pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
+        // argument count is the last required field
+        if buffer.len() >= Self::REQUIRED_FIELDS_LENGTH {
+            let argument_count = buffer[8] as usize;
+            let argument_lengths_end = Self::ARGUMENT_LENGTHS_OFFSET + argument_count;
+
+            // also ensure that all argument lengths are present
+            if buffer.len() >= argument_lengths_end {
+                let user_information_length =
+                    UserInformation::extract_values_length(&buffer[5..8])?;
+                let encoded_arguments_length: usize = buffer
+                    [Self::ARGUMENT_LENGTHS_OFFSET..argument_lengths_end]
+                    .iter()
+                    .map(|&length| length as usize)
+                    .sum();
+
+                // SAFETY: the sum of at most 256 single-byte lengths & a few constants is guaranteed to fit in a u32
+                Ok(u32::try_from(
+                    argument_lengths_end + user_information_length + encoded_arguments_length,
+                )
+                .unwrap())
+            } else {
+                Err(DeserializeError::UnexpectedEnd)
+            }
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
         }
     }
 }
@@ -147,7 +221,7 @@ impl Serialize for Request<'_> {
             self.user_information
                 .serialize_field_lengths(&mut buffer[5..8])?;
 
-            let argument_count = self.arguments.argument_count() as usize;
+            let argument_count = self.arguments.argument_count();
 
             // body starts after the required fields & the argument lengths (1 byte per argument)
             let body_start = Self::ARGUMENT_LENGTHS_OFFSET + argument_count;
@@ -187,6 +261,49 @@ impl Serialize for Request<'_> {
     }
 }
 
+impl<'raw> Deserialize<'raw> for Request<'raw> {
+    fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
+        let total_length = Self::extract_total_length(buffer)? as usize;
+
+        // the provided buffer is sliced to the length reported in the packet header in Packet::deserialize_body(),
+        // so we can compare against it this way
+        let length_from_header = buffer.len();
+
+        if total_length == length_from_header {
+            let flags = Flags::deserialize(buffer[0])?;
+            let authentication_method = AuthenticationMethod::try_from(buffer[1])?;
+            let authentication = AuthenticationContext::deserialize(&buffer[2..5])?;
+
+            // user information values come after the argument lengths
+            let argument_count = buffer[8] as usize;
+            let body_start = Self::ARGUMENT_LENGTHS_OFFSET + argument_count;
+            let user_information =
+                UserInformation::deserialize(&buffer[5..8], &buffer[body_start..])?;
+
+            // arguments occupy the rest of the buffer, and are only validated here; they're decoded lazily when iterated over
+            let arguments_start =
+                body_start + UserInformation::extract_values_length(&buffer[5..8])?;
+            let arguments_info = ArgumentsInfo::new(
+                &buffer[Self::ARGUMENT_LENGTHS_OFFSET..body_start],
+                &buffer[arguments_start..total_length],
+            )?;
+
+            Ok(Self {
+                flags,
+                authentication_method,
+                authentication,
+                user_information,
+                arguments: PacketArguments::Raw(arguments_info),
+            })
+        } else {
+            Err(DeserializeError::WrongBodyBufferSize {
+                expected: total_length,
+                buffer_size: length_from_header,
+            })
+        }
+    }
+}
+
 /// The server's reply status in an accounting session.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, TryFromPrimitive)]
@@ -252,10 +369,28 @@ struct ReplyFieldLengths {
     total_length: u32,
 }
 
-impl Reply<'_> {
+impl<'packet> Reply<'packet> {
     /// Offset of the server message in an accounting reply packet body, if present.
     const SERVER_MESSAGE_OFFSET: usize = 5;
 
+    /// Assembles an accounting reply packet from its fields, performing length checks on the server message
+    /// and data fields to ensure encodable lengths.
+    pub fn new(
+        status: Status,
+        server_message: FieldText<'packet>,
+        data: FieldText<'packet>,
+    ) -> Option<Self> {
+        if u16::try_from(server_message.len()).is_ok() && u16::try_from(data.len()).is_ok() {
+            Some(Self {
+                status,
+                server_message,
+                data,
+            })
+        } else {
+            None
+        }
+    }
+
     /// Determines how long a raw reply packet is, if applicable, based on various lengths stored in the body "header."
     pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
         if buffer.len() >= Self::REQUIRED_FIELDS_LENGTH {
@@ -299,6 +434,45 @@ impl PacketBody for Reply<'_> {
     const REQUIRED_FIELDS_LENGTH: usize = Status::WIRE_SIZE + 4;
 }
 
+impl Serialize for Reply<'_> {
+    fn wire_size(&self) -> usize {
+        Self::REQUIRED_FIELDS_LENGTH + self.server_message.len() + self.data.len()
+    }
+
+    fn serialize_into_buffer(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        let wire_size = self.wire_size();
+
+        if buffer.len() >= wire_size {
+            // field lengths are verified to fit in a u16 in new(), but verify anyways
+            let server_message_len = self.server_message.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[..2], server_message_len);
+
+            let data_len = self.data.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[2..4], data_len);
+
+            buffer[4] = self.status as u8;
+
+            let data_offset = Self::SERVER_MESSAGE_OFFSET + server_message_len as usize;
+            buffer[Self::SERVER_MESSAGE_OFFSET..data_offset]
+                .copy_from_slice(self.server_message.as_bytes());
+
+            let actual_written_len = data_offset + data_len as usize;
+            buffer[data_offset..actual_written_len].copy_from_slice(self.data.as_bytes());
+
+            if actual_written_len == wire_size {
+                Ok(actual_written_len)
+            } else {
+                Err(SerializeError::LengthMismatch {
+                    expected: wire_size,
+                    actual: actual_written_len,
+                })
+            }
+        } else {
+            Err(SerializeError::NotEnoughSpace)
+        }
+    }
+}
+
 impl<'raw> Deserialize<'raw> for Reply<'raw> {
     fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
         let extracted_lengths = Self::extract_field_lengths(buffer)?;
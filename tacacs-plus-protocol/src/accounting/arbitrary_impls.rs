@@ -0,0 +1,30 @@
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use super::{Reply, Request, Status};
+
+impl<'a> Arbitrary<'a> for Status {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        #[allow(deprecated)]
+        let statuses = [Self::Success, Self::Error, Self::Follow];
+
+        u.choose(&statuses).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Request<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            flags: u.arbitrary()?,
+            authentication_method: u.arbitrary()?,
+            authentication: u.arbitrary()?,
+            user_information: u.arbitrary()?,
+            arguments: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Reply<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?).ok_or(Error::IncorrectFormat)
+    }
+}
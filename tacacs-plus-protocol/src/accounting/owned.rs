@@ -1,9 +1,59 @@
 use std::string::String;
 use std::string::ToString;
+use std::vec::Vec;
 
-use super::{Reply, Status};
+use super::{Flags, Reply, Request, Status};
 use crate::owned::FromBorrowedBody;
 use crate::sealed::Sealed;
+use crate::{Argument, AuthenticationContext, AuthenticationMethod};
+
+/// An owned version of a [`Request`](super::Request).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestOwned {
+    /// The kind of accounting record this request contains.
+    pub flags: Flags,
+
+    /// The method used to authenticate to the TACACS+ client.
+    pub authentication_method: AuthenticationMethod,
+
+    /// Other information about the authentication of the user.
+    pub authentication: AuthenticationContext,
+
+    /// The user connected to the client.
+    pub user: String,
+
+    /// The port the user is connected to.
+    pub port: String,
+
+    /// The remote address the user is connecting from.
+    pub remote_address: String,
+
+    /// The arguments included in the accounting record.
+    pub arguments: Vec<Argument<'static>>,
+}
+
+impl Sealed for RequestOwned {}
+
+impl FromBorrowedBody for RequestOwned {
+    type Borrowed<'b> = Request<'b>;
+
+    fn from_borrowed(borrowed: &Self::Borrowed<'_>) -> Self {
+        let arguments_vec = borrowed
+            .iter_arguments()
+            .map(Argument::into_owned)
+            .collect();
+
+        RequestOwned {
+            flags: borrowed.flags,
+            authentication_method: borrowed.authentication_method,
+            authentication: borrowed.authentication,
+            user: borrowed.user_information.user().to_string(),
+            port: borrowed.user_information.port().to_string(),
+            remote_address: borrowed.user_information.remote_address().to_string(),
+            arguments: arguments_vec,
+        }
+    }
+}
 
 /// An owned version of a [`Reply`](super::Reply).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1,4 +1,5 @@
 use super::*;
+use crate::arguments::PacketArguments;
 use crate::packet::xor_body_with_pad;
 use crate::FieldText;
 use crate::{
@@ -34,7 +35,7 @@ fn serialize_request_body_with_argument() {
             FieldText::assert("127.10.0.100"),
         )
         .unwrap(),
-        arguments,
+        arguments: PacketArguments::Provided(arguments),
     };
 
     let mut buffer = [0u8; 50];
@@ -98,7 +99,7 @@ fn serialize_full_request_packet() {
             FieldText::assert("10.10.10.10"),
         )
         .unwrap(),
-        arguments,
+        arguments: PacketArguments::Provided(arguments),
     };
 
     let session_id = 298734923;
@@ -150,6 +151,157 @@ fn serialize_full_request_packet() {
     assert_eq!(&buffer[..packet_size], expected.as_slice());
 }
 
+#[test]
+fn deserialize_request_watchdog_update() {
+    let mut raw_body = array_vec!([u8; 60]);
+    raw_body.extend_from_slice(&[
+        0x08 | 0x02, // watchdog & start flags set
+        0x05,        // authentication method: local
+        1,           // privilege level
+        0x02,        // authentication type: PAP
+        0x01,        // authentication service: login
+        4,           // user length
+        4,           // port length
+        0,           // remote address length
+        2,           // argument count
+        11,          // argument 1 length
+        14,          // argument 2 length
+    ]);
+
+    // user information
+    raw_body.extend_from_slice(b"user");
+    raw_body.extend_from_slice(b"tty3");
+
+    // arguments
+    raw_body.extend_from_slice(b"task_id=123");
+    raw_body.extend_from_slice(b"elapsed_time=5");
+
+    let parsed = Request::deserialize_from_buffer(&raw_body)
+        .expect("request deserialization should have succeeded");
+
+    assert_eq!(parsed.flags(), Flags::WatchdogUpdate);
+    assert_eq!(parsed.authentication_method(), AuthenticationMethod::Local);
+    assert_eq!(
+        parsed.authentication(),
+        AuthenticationContext {
+            privilege_level: PrivilegeLevel::new(1).unwrap(),
+            authentication_type: AuthenticationType::Pap,
+            service: AuthenticationService::Login,
+        }
+    );
+    assert_eq!(parsed.user_information().user(), "user");
+
+    let mut arguments_iter = parsed.iter_arguments();
+    assert_eq!(arguments_iter.len(), 2);
+    assert_eq!(
+        arguments_iter.next(),
+        Some(Argument::new(FieldText::assert("task_id"), FieldText::assert("123"), true).unwrap())
+    );
+    assert_eq!(
+        arguments_iter.next(),
+        Some(
+            Argument::new(
+                FieldText::assert("elapsed_time"),
+                FieldText::assert("5"),
+                true
+            )
+            .unwrap()
+        )
+    );
+    assert_eq!(arguments_iter.next(), None);
+}
+
+#[test]
+fn deserialize_request_start_and_stop_flags() {
+    let raw_body = [
+        0x02 | 0x04, // start & stop flags both set (invalid)
+        0x01,        // authentication method: none
+        0,           // privilege level
+        0x00,        // authentication type: not set
+        0x01,        // authentication service: login
+        0,           // user length
+        0,           // port length
+        0,           // remote address length
+        0,           // argument count
+    ];
+
+    assert_eq!(
+        Request::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidBodyFlags(0x06))
+    );
+}
+
+#[test]
+fn deserialize_request_unknown_flag() {
+    let raw_body = [
+        0x01, // flag not defined in RFC8907
+        0x01, // authentication method: none
+        0,    // privilege level
+        0x00, // authentication type: not set
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        0,    // argument count
+    ];
+
+    assert_eq!(
+        Request::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidBodyFlags(0x01))
+    );
+}
+
+#[test]
+fn raw_flags_round_trip() {
+    for flags in [
+        Flags::StartRecord,
+        Flags::StopRecord,
+        Flags::WatchdogNoUpdate,
+        Flags::WatchdogUpdate,
+    ] {
+        assert_eq!(Flags::try_from(RawFlags::from(flags)), Ok(flags));
+    }
+}
+
+#[test]
+fn deserialize_full_request_packet() {
+    let session_id: u32 = 7777;
+    let mut raw_packet = array_vec!([u8; 60]);
+
+    // HEADER
+    raw_packet.extend_from_slice(&[
+        0xc << 4, // version
+        3,        // accounting packet
+        1,        // sequence number
+        1,        // unencrypted flag set
+    ]);
+    raw_packet.extend_from_slice(session_id.to_be_bytes().as_slice());
+    raw_packet.extend_from_slice(25_u32.to_be_bytes().as_slice()); // body length
+
+    // BODY
+    raw_packet.extend_from_slice(&[
+        0x04, // stop flag set
+        0x06, // authentication method: TACACS+
+        0,    // privilege level
+        0x00, // authentication type: not set
+        0x01, // authentication service: login
+        3,    // user length
+        0,    // port length
+        0,    // remote address length
+        1,    // argument count
+        12,   // argument 1 length
+    ]);
+    raw_packet.extend_from_slice(b"bob"); // user
+    raw_packet.extend_from_slice(b"stop_time=10"); // argument
+
+    let packet: Packet<Request> = Packet::deserialize_unobfuscated(&raw_packet)
+        .expect("packet deserialization should have succeeded");
+
+    assert_eq!(packet.header().session_id(), session_id);
+    assert_eq!(packet.body().flags(), Flags::StopRecord);
+    assert_eq!(packet.body().iter_arguments().len(), 1);
+}
+
 #[test]
 fn deserialize_reply_all_fields() {
     let mut body_raw = array_vec!([u8; 70]);
@@ -312,3 +464,85 @@ fn serialize_request_packet_obfuscated() {
     // ensure obfuscation is correct
     assert_eq!(&buffer[..serialized_length], &expected[..serialized_length]);
 }
+
+#[test]
+fn serialize_reply_all_fields() {
+    let reply = Reply::new(
+        Status::Success,
+        FieldText::assert("logged"),
+        FieldText::assert("data"),
+    )
+    .expect("reply fields should be valid");
+
+    let mut buffer = [0; 20];
+    let serialized_length = reply
+        .serialize_into_buffer(&mut buffer)
+        .expect("reply serialization should have succeeded");
+
+    let mut expected = array_vec!([u8; 20]);
+    expected.extend_from_slice(&[
+        0, 6, // server message length
+        0, 4,    // data length
+        0x01, // status: success
+    ]);
+    expected.extend_from_slice(b"logged");
+    expected.extend_from_slice(b"data");
+
+    assert_eq!(&buffer[..serialized_length], expected.as_slice());
+}
+
+#[test]
+fn serialize_reply_not_enough_space() {
+    let reply = Reply::new(
+        Status::Error,
+        FieldText::assert("this won't fit"),
+        FieldText::assert(""),
+    )
+    .expect("reply fields should be valid");
+
+    let mut buffer = [0; 10];
+    assert_eq!(
+        reply.serialize_into_buffer(&mut buffer),
+        Err(SerializeError::NotEnoughSpace)
+    );
+}
+
+#[test]
+fn reply_data_too_long() {
+    let long_data = [b'a'; u16::MAX as usize + 1];
+    let long_data_text = FieldText::try_from(long_data.as_slice()).unwrap();
+
+    assert!(
+        Reply::new(Status::Success, FieldText::assert(""), long_data_text).is_none(),
+        "overly long data field should be rejected"
+    );
+}
+
+#[test]
+fn serialize_obfuscated_reply_packet_round_trip() {
+    let key = b"accounting";
+    let packet = Packet::new(
+        HeaderInfo::new(
+            Version::new(MajorVersion::RFC8907, MinorVersion::Default),
+            2,
+            PacketFlags::SINGLE_CONNECTION,
+            98765,
+        ),
+        Reply::new(
+            Status::Success,
+            FieldText::assert("recorded"),
+            FieldText::assert(""),
+        )
+        .expect("reply fields should be valid"),
+    );
+
+    let mut buffer = [0; 40];
+    let packet_length = packet
+        .clone()
+        .serialize(key, &mut buffer)
+        .expect("packet serialization should have succeeded");
+
+    let parsed: Packet<Reply> = Packet::deserialize(key, &mut buffer[..packet_length])
+        .expect("serialized packet should deserialize");
+    assert_eq!(parsed, packet);
+}
@@ -0,0 +1,67 @@
+//! [`Arbitrary`] implementations for types that have invariants which a derived implementation wouldn't uphold.
+//!
+//! Implementations for packet bodies live alongside their respective modules, since they need access to private fields.
+
+use std::string::String;
+use std::vec::Vec;
+
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use crate::arguments::PacketArguments;
+use crate::{
+    Argument, FieldText, HeaderInfo, Packet, PacketBody, PacketFlags, PrivilegeLevel,
+    UserInformation,
+};
+
+impl<'a> Arbitrary<'a> for PacketFlags {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_bits_truncate(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for PrivilegeLevel {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // SAFETY: the generated level is guaranteed to be in the valid range
+        Ok(Self::new(u.int_in_range(0..=15)?).unwrap())
+    }
+}
+
+impl<'a> Arbitrary<'a> for FieldText<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // escaping non-printable characters produces valid text from any string
+        Ok(FieldText::from_string_lossy(String::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for UserInformation<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?).ok_or(Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Argument<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?)
+            .map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for PacketArguments<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // at most u8::MAX arguments can be encoded in a packet
+        let arguments = u
+            .arbitrary_iter::<Argument<'a>>()?
+            .take(u8::MAX.into())
+            .collect::<Result<Vec<_>>>()?;
+
+        // provided arguments are borrowed, so the generated ones are kept in a variant that owns them instead
+        Ok(Self::Owned(arguments))
+    }
+}
+
+impl<'a, B: PacketBody + Arbitrary<'a>> Arbitrary<'a> for Packet<B> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let header: HeaderInfo = u.arbitrary()?;
+        Ok(Self::new(header, u.arbitrary()?))
+    }
+}
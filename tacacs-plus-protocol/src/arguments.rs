@@ -1,5 +1,7 @@
 use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::iter::zip;
+use core::slice;
 
 use getset::{CopyGetters, Getters, Setters};
 
@@ -298,3 +300,265 @@ impl<'args> AsRef<[Argument<'args>]> for Arguments<'args> {
         self.0
     }
 }
+
+/// Raw argument lengths and encoded values, as stored in a received packet body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ArgumentsInfo<'raw> {
+    /// The lengths of each argument, one byte per argument.
+    argument_lengths: &'raw [u8],
+
+    /// The encoded argument values, concatenated together.
+    arguments_buffer: &'raw [u8],
+}
+
+impl<'raw> ArgumentsInfo<'raw> {
+    /// Bundles together raw argument lengths and values, ensuring they represent a valid set of arguments.
+    ///
+    /// `argument_lengths` should have been sliced based on the encoded argument count, and `arguments_buffer`
+    /// must be exactly as long as the sum of the argument lengths.
+    pub(crate) fn new(
+        argument_lengths: &'raw [u8],
+        arguments_buffer: &'raw [u8],
+    ) -> Result<Self, DeserializeError> {
+        let mut argument_start = 0;
+
+        for &length in argument_lengths {
+            let argument_end = argument_start + length as usize;
+            let raw_argument = arguments_buffer
+                .get(argument_start..argument_end)
+                .ok_or(DeserializeError::UnexpectedEnd)?;
+            argument_start = argument_end;
+
+            // we don't care about the actual argument here, but the specific error should be kept
+            Argument::deserialize(raw_argument)?;
+        }
+
+        if argument_start == arguments_buffer.len() {
+            Ok(Self {
+                argument_lengths,
+                arguments_buffer,
+            })
+        } else {
+            Err(DeserializeError::WrongBodyBufferSize {
+                expected: argument_start,
+                buffer_size: arguments_buffer.len(),
+            })
+        }
+    }
+
+    /// Returns the number of arguments.
+    fn argument_count(&self) -> usize {
+        self.argument_lengths.len()
+    }
+
+    /// Returns the size of these arguments on the wire, including the argument count & lengths.
+    fn wire_size(&self) -> usize {
+        1 + self.argument_lengths.len() + self.arguments_buffer.len()
+    }
+
+    /// Copies the argument count & raw argument lengths into a buffer.
+    fn serialize_count_and_lengths(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        let argument_count = self.argument_count();
+
+        // strict greater than to allow room for encoded argument count itself
+        if buffer.len() > argument_count {
+            buffer[0] = argument_count.try_into()?;
+            buffer[1..1 + argument_count].copy_from_slice(self.argument_lengths);
+
+            Ok(1 + argument_count)
+        } else {
+            Err(SerializeError::NotEnoughSpace)
+        }
+    }
+
+    /// Copies the raw encoded argument values into a buffer.
+    fn serialize_encoded_values(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        let values_length = self.arguments_buffer.len();
+
+        if buffer.len() >= values_length {
+            buffer[..values_length].copy_from_slice(self.arguments_buffer);
+            Ok(values_length)
+        } else {
+            Err(SerializeError::NotEnoughSpace)
+        }
+    }
+}
+
+/// The arguments of a packet body, which are either provided directly or
+/// backed by a buffer that the packet was deserialized from.
+// generated arguments own their storage, so the type can only be Copy without them
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "arbitrary"), derive(Copy))]
+pub(crate) enum PacketArguments<'packet> {
+    /// Arguments provided when constructing a packet.
+    Provided(Arguments<'packet>),
+
+    /// Arguments from a deserialized packet.
+    Raw(ArgumentsInfo<'packet>),
+
+    /// Arguments generated via [`Arbitrary`](arbitrary::Arbitrary), which have to own the list they're stored in.
+    #[cfg(feature = "arbitrary")]
+    Owned(std::vec::Vec<Argument<'packet>>),
+}
+
+impl PacketArguments<'_> {
+    /// Returns the number of arguments.
+    pub(crate) fn argument_count(&self) -> usize {
+        match self {
+            Self::Provided(arguments) => arguments.argument_count() as usize,
+            Self::Raw(info) => info.argument_count(),
+            #[cfg(feature = "arbitrary")]
+            Self::Owned(arguments) => arguments.len(),
+        }
+    }
+
+    /// Returns the size of the arguments on the wire, including the argument count & lengths.
+    pub(crate) fn wire_size(&self) -> usize {
+        match self {
+            Self::Provided(arguments) => arguments.wire_size(),
+            Self::Raw(info) => info.wire_size(),
+            #[cfg(feature = "arbitrary")]
+            Self::Owned(arguments) => Arguments(arguments).wire_size(),
+        }
+    }
+
+    /// Serializes the argument count & argument lengths into a buffer.
+    pub(crate) fn serialize_count_and_lengths(
+        &self,
+        buffer: &mut [u8],
+    ) -> Result<usize, SerializeError> {
+        match self {
+            Self::Provided(arguments) => arguments.serialize_count_and_lengths(buffer),
+            Self::Raw(info) => info.serialize_count_and_lengths(buffer),
+            #[cfg(feature = "arbitrary")]
+            Self::Owned(arguments) => Arguments(arguments).serialize_count_and_lengths(buffer),
+        }
+    }
+
+    /// Serializes the encoded argument values into a buffer.
+    pub(crate) fn serialize_encoded_values(
+        &self,
+        buffer: &mut [u8],
+    ) -> Result<usize, SerializeError> {
+        match self {
+            Self::Provided(arguments) => arguments.serialize_encoded_values(buffer),
+            Self::Raw(info) => info.serialize_encoded_values(buffer),
+            #[cfg(feature = "arbitrary")]
+            Self::Owned(arguments) => Arguments(arguments).serialize_encoded_values(buffer),
+        }
+    }
+
+    /// Returns an iterator over the contained arguments.
+    pub(crate) fn iter(&self) -> ArgumentsIterator<'_> {
+        match self {
+            Self::Provided(arguments) => ArgumentsIterator::provided(arguments.0),
+            Self::Raw(info) => ArgumentsIterator::raw(info),
+            #[cfg(feature = "arbitrary")]
+            Self::Owned(arguments) => ArgumentsIterator::provided(arguments),
+        }
+    }
+}
+
+// arguments are compared by value, regardless of whether they were provided directly or deserialized
+impl PartialEq for PacketArguments<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for PacketArguments<'_> {}
+
+impl Hash for PacketArguments<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // this matches the Hash impl for slices, which prepend their length
+        state.write_usize(self.argument_count());
+        self.iter().for_each(|argument| argument.hash(state));
+    }
+}
+
+/// An iterator over the arguments in a packet.
+#[derive(Debug, Clone)]
+pub struct ArgumentsIterator<'iter>(IteratorInner<'iter>);
+
+#[derive(Debug, Clone)]
+enum IteratorInner<'iter> {
+    /// Iteration over arguments provided directly to a packet constructor.
+    Provided(slice::Iter<'iter, Argument<'iter>>),
+
+    /// Iteration over arguments that are still in their wire encoding.
+    Raw {
+        /// Argument information, including argument lengths.
+        arguments_info: &'iter ArgumentsInfo<'iter>,
+
+        /// Position of the next argument, as if into a zero-indexed array of complete arguments.
+        next_argument_number: usize,
+
+        /// Offset of an argument within the buffer.
+        next_offset: usize,
+    },
+}
+
+impl<'iter> ArgumentsIterator<'iter> {
+    /// Creates an iterator over a slice of already-constructed arguments.
+    fn provided(arguments: &'iter [Argument<'iter>]) -> Self {
+        Self(IteratorInner::Provided(arguments.iter()))
+    }
+
+    /// Creates an iterator that decodes arguments from their raw encoding as needed.
+    pub(crate) fn raw(arguments_info: &'iter ArgumentsInfo<'iter>) -> Self {
+        Self(IteratorInner::Raw {
+            arguments_info,
+            next_argument_number: 0,
+            next_offset: 0,
+        })
+    }
+}
+
+impl<'iter> Iterator for ArgumentsIterator<'iter> {
+    type Item = Argument<'iter>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            IteratorInner::Provided(iter) => iter.next().cloned(),
+            IteratorInner::Raw {
+                arguments_info,
+                next_argument_number,
+                next_offset,
+            } => {
+                // get encoded argument from buffer based on stored offset into buffer/length
+                let next_length =
+                    *arguments_info.argument_lengths.get(*next_argument_number)? as usize;
+                let raw_argument = arguments_info
+                    .arguments_buffer
+                    .get(*next_offset..*next_offset + next_length)?;
+
+                // update iterator state
+                *next_argument_number += 1;
+                *next_offset += next_length;
+
+                // NOTE: this should always be Some, since the validity of arguments is checked in ArgumentsInfo::new()
+                Argument::deserialize(raw_argument).ok()
+            }
+        }
+    }
+
+    // required for ExactSizeIterator impl
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            IteratorInner::Provided(iter) => iter.size_hint(),
+            IteratorInner::Raw {
+                arguments_info,
+                next_argument_number,
+                ..
+            } => {
+                let remaining_size = arguments_info.argument_count() - next_argument_number;
+
+                // these are asserted to be equal in the default ExactSizeIterator::len() implementation
+                (remaining_size, Some(remaining_size))
+            }
+        }
+    }
+}
+
+// Gives ArgumentsIterator a .len() method
+impl ExactSizeIterator for ArgumentsIterator<'_> {}
@@ -19,15 +19,19 @@ mod tests;
 #[cfg(feature = "std")]
 mod owned;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+
 mod data;
 pub use data::{DataTooLong, PacketData};
 
 #[cfg(feature = "std")]
-pub use owned::ReplyOwned;
+pub use owned::{ContinueOwned, ReplyOwned, StartOwned};
 
 /// The authentication action, as indicated upon initiation of an authentication session.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Action {
     /// Login request.
     Login = 0x01,
@@ -48,6 +52,13 @@ impl Action {
     const WIRE_SIZE: usize = 1;
 }
 
+#[doc(hidden)]
+impl From<TryFromPrimitiveError<Action>> for DeserializeError {
+    fn from(value: TryFromPrimitiveError<Action>) -> Self {
+        Self::InvalidAction(value.number)
+    }
+}
+
 /// The authentication status, as returned by a TACACS+ server.
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, TryFromPrimitive)]
@@ -91,11 +102,22 @@ impl From<TryFromPrimitiveError<Status>> for DeserializeError {
 }
 
 /// An authentication start packet, used to initiate an authentication session.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, CopyGetters)]
 pub struct Start<'packet> {
+    /// Gets the authentication action requested by the client.
+    #[getset(get_copy = "pub")]
     action: Action,
+
+    /// Gets the privilege level, authentication type and service of this session.
+    #[getset(get_copy = "pub")]
     authentication: AuthenticationContext,
+
+    /// Gets the information about the user connected to the client.
+    #[getset(get = "pub")]
     user_information: UserInformation<'packet>,
+
+    /// Gets the authentication data included in the packet, if any.
+    #[getset(get = "pub")]
     data: Option<PacketData<'packet>>,
 }
 
@@ -114,6 +136,12 @@ pub enum BadStart {
     IncompatibleActionAndType,
 }
 
+impl From<BadStart> for DeserializeError {
+    fn from(value: BadStart) -> Self {
+        Self::InvalidStart(value)
+    }
+}
+
 impl fmt::Display for BadStart {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -129,6 +157,9 @@ impl fmt::Display for BadStart {
 }
 
 impl<'packet> Start<'packet> {
+    /// Offset of the user information field values within a start packet body.
+    const USER_INFORMATION_OFFSET: usize = 8;
+
     /// Initializes a new start packet with the provided fields and an empty data field.
     pub fn new(
         action: Action,
@@ -257,6 +288,50 @@ impl Serialize for Start<'_> {
     }
 }
 
+impl<'raw> Deserialize<'raw> for Start<'raw> {
+    fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
+        // data length is the last required field, so all of them have to be present to determine the full body length
+        if buffer.len() >= Self::REQUIRED_FIELDS_LENGTH {
+            let user_information_length = UserInformation::extract_values_length(&buffer[4..7])?;
+            let data_length = buffer[7] as usize;
+
+            let total_length = Self::REQUIRED_FIELDS_LENGTH + user_information_length + data_length;
+
+            // buffer is sliced to length reported in packet header in Packet::deserialize_body(), so we can compare against
+            // it using the buffer length
+            let length_from_header = buffer.len();
+
+            if total_length == length_from_header {
+                let action = Action::try_from(buffer[0])?;
+                let authentication = AuthenticationContext::deserialize(&buffer[1..4])?;
+                let user_information = UserInformation::deserialize(
+                    &buffer[4..7],
+                    &buffer[Self::USER_INFORMATION_OFFSET..],
+                )?;
+
+                // an empty data field is treated the same as an absent one, as they're encoded identically
+                let data_start = Self::USER_INFORMATION_OFFSET + user_information_length;
+                let data = if data_length > 0 {
+                    // SAFETY: the data length is read from a single byte, so it's guaranteed to fit in a PacketData
+                    Some(PacketData::try_from(&buffer[data_start..total_length]).unwrap())
+                } else {
+                    None
+                };
+
+                // use constructor to ensure action/authentication type are valid together, as is done when serializing
+                Self::new(action, authentication, user_information, data).map_err(Into::into)
+            } else {
+                Err(DeserializeError::WrongBodyBufferSize {
+                    expected: total_length,
+                    buffer_size: length_from_header,
+                })
+            }
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
+    }
+}
+
 bitflags! {
     /// Flags received in an authentication reply packet.
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
@@ -274,7 +349,7 @@ impl ReplyFlags {
 
 crate::util::bitflags_display_impl!(ReplyFlags);
 
-/// An authentication reply packet received from a server.
+/// An authentication reply packet, as sent by a server.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, CopyGetters)]
 pub struct Reply<'packet> {
     /// Gets the status of this authentication exchange, as returned from the server.
@@ -300,10 +375,29 @@ struct ReplyFieldLengths {
     total_length: u32,
 }
 
-impl Reply<'_> {
+impl<'packet> Reply<'packet> {
     /// Server message offset within packet body as a zero-based index.
     const SERVER_MESSAGE_OFFSET: usize = 6;
 
+    /// Assembles a reply packet, performing length checks on the server message and data fields to ensure encodable lengths.
+    pub fn new(
+        status: Status,
+        server_message: FieldText<'packet>,
+        data: &'packet [u8],
+        flags: ReplyFlags,
+    ) -> Option<Self> {
+        if u16::try_from(server_message.len()).is_ok() && u16::try_from(data.len()).is_ok() {
+            Some(Self {
+                status,
+                server_message,
+                data,
+                flags,
+            })
+        } else {
+            None
+        }
+    }
+
     /// Attempts to extract the claimed reply packed body length from a buffer.
     pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
         Self::extract_field_lengths(buffer).map(|lengths| lengths.total_length)
@@ -340,6 +434,48 @@ impl PacketBody for Reply<'_> {
     const REQUIRED_FIELDS_LENGTH: usize = Status::WIRE_SIZE + ReplyFlags::WIRE_SIZE + 4;
 }
 
+impl Serialize for Reply<'_> {
+    fn wire_size(&self) -> usize {
+        Self::REQUIRED_FIELDS_LENGTH + self.server_message.len() + self.data.len()
+    }
+
+    fn serialize_into_buffer(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        let wire_size = self.wire_size();
+
+        if buffer.len() >= wire_size {
+            buffer[0] = self.status as u8;
+            buffer[1] = self.flags.bits();
+
+            // field lengths are verified to fit in a u16 in new(), but verify anyways
+            let server_message_len = self.server_message.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[2..4], server_message_len);
+
+            let data_len = self.data.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[4..6], data_len);
+
+            // data goes directly after the server message
+            let data_offset = Self::SERVER_MESSAGE_OFFSET + server_message_len as usize;
+            buffer[Self::SERVER_MESSAGE_OFFSET..data_offset]
+                .copy_from_slice(self.server_message.as_bytes());
+            buffer[data_offset..data_offset + data_len as usize].copy_from_slice(self.data);
+
+            let actual_written_len =
+                Self::REQUIRED_FIELDS_LENGTH + server_message_len as usize + data_len as usize;
+
+            if actual_written_len == wire_size {
+                Ok(actual_written_len)
+            } else {
+                Err(SerializeError::LengthMismatch {
+                    expected: wire_size,
+                    actual: actual_written_len,
+                })
+            }
+        } else {
+            Err(SerializeError::NotEnoughSpace)
+        }
+    }
+}
+
 // Hide from docs, as this is meant for internal use only
 #[doc(hidden)]
 impl<'raw> Deserialize<'raw> for Reply<'raw> {
@@ -392,14 +528,32 @@ bitflags! {
 
 crate::util::bitflags_display_impl!(ContinueFlags);
 
+impl ContinueFlags {
+    /// Number of bytes continue flags occupy on the wire.
+    const WIRE_SIZE: usize = 1;
+}
+
 /// A continue packet potentially sent as part of an authentication session.
-#[derive(PartialEq, Eq, Clone, Debug, Hash)]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, CopyGetters)]
+#[getset(get_copy = "pub")]
 pub struct Continue<'packet> {
+    /// Gets the response to a server prompt (e.g., a username or password), if present.
     user_message: Option<&'packet [u8]>,
+
+    /// Gets the domain-specific data sent by the client, if present.
     data: Option<&'packet [u8]>,
+
+    /// Gets the flags set in this continue packet.
     flags: ContinueFlags,
 }
 
+/// The variable-length field lengths of a (raw) continue packet body, as well as its total length.
+struct ContinueFieldLengths {
+    user_message_length: u16,
+    data_length: u16,
+    total_length: u32,
+}
+
 impl<'packet> Continue<'packet> {
     /// Offset of the user message within a continue packet body, if present.
     const USER_MESSAGE_OFFSET: usize = 5;
@@ -422,13 +576,40 @@ impl<'packet> Continue<'packet> {
             None
         }
     }
+
+    /// Attempts to extract the claimed continue packet body length from a buffer.
+    pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
+        Self::extract_field_lengths(buffer).map(|lengths| lengths.total_length)
+    }
+
+    /// Extracts the user message and data field lengths from a buffer, treating it as if it were a serialized continue packet body.
+    fn extract_field_lengths(buffer: &[u8]) -> Result<ContinueFieldLengths, DeserializeError> {
+        // flags are the last required field, but the lengths are all we need here
+        if buffer.len() >= Self::REQUIRED_FIELDS_LENGTH {
+            let user_message_length = NetworkEndian::read_u16(&buffer[..2]);
+            let data_length = NetworkEndian::read_u16(&buffer[2..4]);
+
+            // SAFETY: REQUIRED_FIELDS_LENGTH as defined is guaranteed to fit in a u32
+            let total_length = u32::try_from(Self::REQUIRED_FIELDS_LENGTH).unwrap()
+                + u32::from(user_message_length)
+                + u32::from(data_length);
+
+            Ok(ContinueFieldLengths {
+                user_message_length,
+                data_length,
+                total_length,
+            })
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
+    }
 }
 
 impl PacketBody for Continue<'_> {
     const TYPE: PacketType = PacketType::Authentication;
 
     // 2 bytes each for user message & data length; 1 byte for flags
-    const REQUIRED_FIELDS_LENGTH: usize = 5;
+    const REQUIRED_FIELDS_LENGTH: usize = 4 + ContinueFlags::WIRE_SIZE;
 }
 
 impl Serialize for Continue<'_> {
@@ -481,3 +662,39 @@ impl Serialize for Continue<'_> {
         }
     }
 }
+
+impl<'raw> Deserialize<'raw> for Continue<'raw> {
+    fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
+        let field_lengths = Self::extract_field_lengths(buffer)?;
+
+        // buffer is sliced to length reported in packet header in Packet::deserialize_body(), so we can compare against
+        // it using the buffer length
+        let length_from_header = buffer.len();
+
+        if field_lengths.total_length as usize == length_from_header {
+            let flag_byte = buffer[4];
+            let flags = ContinueFlags::from_bits(flag_byte)
+                .ok_or(DeserializeError::InvalidBodyFlags(flag_byte))?;
+
+            let data_offset =
+                Self::USER_MESSAGE_OFFSET + field_lengths.user_message_length as usize;
+
+            // empty fields are treated as absent, since the two are encoded identically
+            let user_message = Some(&buffer[Self::USER_MESSAGE_OFFSET..data_offset])
+                .filter(|message| !message.is_empty());
+            let data = Some(&buffer[data_offset..data_offset + field_lengths.data_length as usize])
+                .filter(|data| !data.is_empty());
+
+            Ok(Self {
+                user_message,
+                data,
+                flags,
+            })
+        } else {
+            Err(DeserializeError::WrongBodyBufferSize {
+                expected: field_lengths.total_length as usize,
+                buffer_size: length_from_header,
+            })
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use super::{Continue, ContinueFlags, PacketData, Reply, ReplyFlags, Start, Status};
+
+impl<'a> Arbitrary<'a> for Status {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        #[allow(deprecated)]
+        let statuses = [
+            Self::Pass,
+            Self::Fail,
+            Self::GetData,
+            Self::GetUser,
+            Self::GetPassword,
+            Self::Restart,
+            Self::Error,
+            Self::Follow,
+        ];
+
+        u.choose(&statuses).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for ReplyFlags {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_bits_truncate(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ContinueFlags {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_bits_truncate(u.arbitrary()?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for PacketData<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // data length has to fit in a single byte
+        let length = u.int_in_range(0..=u8::MAX)?;
+
+        // SAFETY: the length was generated to be in the valid range
+        Ok(Self::try_from(u.bytes(length.into())?).unwrap())
+    }
+}
+
+impl<'a> Arbitrary<'a> for Start<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        )
+        .map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Continue<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?).ok_or(Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Reply<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::new(
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+            u.arbitrary()?,
+        )
+        .ok_or(Error::IncorrectFormat)
+    }
+}
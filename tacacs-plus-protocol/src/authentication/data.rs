@@ -1,6 +1,6 @@
 use core::fmt;
 
-#[derive(Debug, Clone, PartialEq, Eq, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum PacketDataInner<'data> {
     Borrowed(&'data [u8]),
 
@@ -10,7 +10,14 @@ enum PacketDataInner<'data> {
 
 impl PartialOrd for PacketDataInner<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        Some(self.as_ref().cmp(other.as_ref()))
+        Some(self.cmp(other))
+    }
+}
+
+// ordering is based on the underlying bytes, regardless of owned/borrowed status
+impl Ord for PacketDataInner<'_> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
     }
 }
 
@@ -3,10 +3,80 @@ use std::string::String;
 use std::string::ToString;
 use std::vec::Vec;
 
-use super::Reply;
+use super::{Action, Continue, ContinueFlags, Reply, Start};
 use super::{ReplyFlags, Status};
 use crate::owned::FromBorrowedBody;
 use crate::sealed::Sealed;
+use crate::AuthenticationContext;
+
+/// An authentication start packet with owned fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StartOwned {
+    /// The authentication action requested by the client.
+    pub action: Action,
+
+    /// The privilege level, authentication type and service of the session.
+    pub authentication: AuthenticationContext,
+
+    /// The user connected to the client.
+    pub user: String,
+
+    /// The port the user is connected to.
+    pub port: String,
+
+    /// The remote address the user is connecting from.
+    pub remote_address: String,
+
+    /// The authentication data included in the packet, if any.
+    pub data: Option<Vec<u8>>,
+}
+
+impl Sealed for StartOwned {}
+
+impl FromBorrowedBody for StartOwned {
+    type Borrowed<'b> = Start<'b>;
+
+    fn from_borrowed(borrowed: &Self::Borrowed<'_>) -> Self {
+        StartOwned {
+            action: borrowed.action,
+            authentication: borrowed.authentication,
+            user: borrowed.user_information.user().to_string(),
+            port: borrowed.user_information.port().to_string(),
+            remote_address: borrowed.user_information.remote_address().to_string(),
+            data: borrowed
+                .data
+                .as_ref()
+                .map(|data| data.as_bytes().to_owned()),
+        }
+    }
+}
+
+/// An authentication continue packet with owned fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContinueOwned {
+    /// The response to a server prompt, if present.
+    pub user_message: Option<Vec<u8>>,
+
+    /// The domain-specific data sent by the client, if present.
+    pub data: Option<Vec<u8>>,
+
+    /// The flags set in the continue packet.
+    pub flags: ContinueFlags,
+}
+
+impl Sealed for ContinueOwned {}
+
+impl FromBorrowedBody for ContinueOwned {
+    type Borrowed<'b> = Continue<'b>;
+
+    fn from_borrowed(borrowed: &Self::Borrowed<'_>) -> Self {
+        ContinueOwned {
+            user_message: borrowed.user_message.map(ToOwned::to_owned),
+            data: borrowed.data.map(ToOwned::to_owned),
+            flags: borrowed.flags,
+        }
+    }
+}
 
 /// An authentication reply packet with owned fields.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
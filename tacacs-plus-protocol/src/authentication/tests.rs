@@ -184,6 +184,219 @@ fn serialize_full_start_packet() {
     assert_eq!(&buffer[..43], expected.as_slice());
 }
 
+#[test]
+fn deserialize_start_with_data() {
+    let mut raw_body = array_vec!([u8; 40]);
+    raw_body.extend_from_slice(&[
+        0x01, // action: login
+        15,   // privilege level
+        0x03, // authentication type: CHAP
+        0x01, // authentication service: login
+        4,    // user length
+        5,    // port length
+        8,    // remote address length
+        3,    // data length
+    ]);
+
+    // user information
+    raw_body.extend_from_slice(b"user");
+    raw_body.extend_from_slice(b"tty01");
+    raw_body.extend_from_slice(b"10.0.0.1");
+
+    // data
+    raw_body.extend_from_slice(&[0x11, 0x22, 0x33]);
+
+    let expected = Start::new(
+        Action::Login,
+        AuthenticationContext {
+            privilege_level: PrivilegeLevel::new(15).unwrap(),
+            authentication_type: AuthenticationType::Chap,
+            service: AuthenticationService::Login,
+        },
+        UserInformation::new(
+            "user",
+            FieldText::assert("tty01"),
+            FieldText::assert("10.0.0.1"),
+        )
+        .unwrap(),
+        Some([0x11, 0x22, 0x33].as_slice().try_into().unwrap()),
+    )
+    .unwrap();
+
+    assert_eq!(Start::deserialize_from_buffer(&raw_body), Ok(expected));
+}
+
+#[test]
+fn deserialize_start_no_data() {
+    let mut raw_body = array_vec!([u8; 30]);
+    raw_body.extend_from_slice(&[
+        0x01, // action: login
+        0,    // privilege level
+        0x01, // authentication type: ASCII
+        0x02, // authentication service: enable
+        0,    // user length (ASCII login can prompt for username later)
+        4,    // port length
+        0,    // remote address length
+        0,    // data length
+    ]);
+    raw_body.extend_from_slice(b"tty5");
+
+    let start = Start::deserialize_from_buffer(&raw_body)
+        .expect("start packet body deserialization should have succeeded");
+
+    assert_eq!(start.action(), Action::Login);
+    assert_eq!(
+        start.authentication().authentication_type,
+        AuthenticationType::Ascii
+    );
+    assert_eq!(start.user_information().user(), "");
+    assert_eq!(start.user_information().port(), &FieldText::assert("tty5"));
+    assert_eq!(start.data(), &None);
+}
+
+#[test]
+fn deserialize_start_incompatible_action_and_type() {
+    let raw_body = [
+        0x04, // action: sendauth
+        1,    // privilege level
+        0x01, // authentication type: ASCII (not valid with sendauth)
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        0,    // data length
+    ];
+
+    assert_eq!(
+        Start::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidStart(
+            BadStart::IncompatibleActionAndType
+        ))
+    );
+}
+
+#[test]
+fn deserialize_start_type_not_set() {
+    let raw_body = [
+        0x01, // action: login
+        1,    // privilege level
+        0x00, // authentication type: not set (invalid in start packets)
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        0,    // data length
+    ];
+
+    assert_eq!(
+        Start::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidStart(BadStart::AuthTypeNotSet))
+    );
+}
+
+#[test]
+fn deserialize_start_bad_privilege_level() {
+    let raw_body = [
+        0x01, // action: login
+        16,   // privilege level (out of range)
+        0x02, // authentication type: PAP
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        0,    // data length
+    ];
+
+    assert_eq!(
+        Start::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidPrivilegeLevel(16))
+    );
+}
+
+#[test]
+fn deserialize_start_wrong_length() {
+    let mut raw_body = array_vec!([u8; 20]);
+    raw_body.extend_from_slice(&[
+        0x01, // action: login
+        1,    // privilege level
+        0x02, // authentication type: PAP
+        0x01, // authentication service: login
+        4,    // user length
+        0,    // port length
+        0,    // remote address length
+        8,    // data length (longer than what's actually there)
+    ]);
+    raw_body.extend_from_slice(b"user");
+    raw_body.extend_from_slice(b"pass");
+
+    assert_eq!(
+        Start::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::WrongBodyBufferSize {
+            expected: 20,
+            buffer_size: 16
+        })
+    );
+}
+
+#[test]
+fn deserialize_full_start_packet() {
+    let session_id: u32 = 1234987;
+    let mut raw_packet = array_vec!([u8; 50]);
+
+    // HEADER
+    raw_packet.extend_from_slice(&[
+        (0xc << 4) | 1, // version (minor v1, for PAP)
+        1,              // authentication packet
+        1,              // sequence number
+        0,              // no flags set
+    ]);
+    raw_packet.extend_from_slice(session_id.to_be_bytes().as_slice());
+    raw_packet.extend_from_slice(26_u32.to_be_bytes().as_slice()); // body length
+
+    // BODY
+    raw_packet.extend_from_slice(&[
+        0x01, // action: login
+        1,    // privilege level
+        0x02, // authentication type: PAP
+        0x01, // authentication service: login
+        8,    // user length
+        2,    // port length
+        0,    // remote address length
+        8,    // data length
+    ]);
+    raw_packet.extend_from_slice(b"username");
+    raw_packet.extend_from_slice(b"49");
+    raw_packet.extend_from_slice(b"password");
+
+    let expected_header = HeaderInfo::new(
+        Version::new(MajorVersion::RFC8907, MinorVersion::V1),
+        1,
+        PacketFlags::empty(),
+        session_id,
+    );
+
+    let secret_key = b"start packet key";
+    crate::packet::xor_body_with_pad(
+        &expected_header,
+        secret_key,
+        &mut raw_packet[HeaderInfo::HEADER_SIZE_BYTES..],
+    );
+
+    let packet: Packet<Start> = Packet::deserialize(secret_key, &mut raw_packet)
+        .expect("packet deserialization should have succeeded");
+
+    assert_eq!(packet.header(), &expected_header);
+
+    let body = packet.body();
+    assert_eq!(body.action(), Action::Login);
+    assert_eq!(body.user_information().user(), "username");
+    assert_eq!(body.user_information().port(), &FieldText::assert("49"));
+    assert_eq!(
+        body.data().as_ref().map(PacketData::as_bytes),
+        Some(b"password".as_slice())
+    );
+}
+
 #[test]
 fn deserialize_reply_pass_both_data_fields() {
     let mut packet_data = array_vec!([u8; 40]);
@@ -374,6 +587,102 @@ fn deserialize_reply_type_mismatch() {
     );
 }
 
+#[test]
+fn serialize_reply_both_fields() {
+    let reply = Reply::new(
+        Status::GetPassword,
+        FieldText::assert("Password: "),
+        &[0xde, 0xad],
+        ReplyFlags::NO_ECHO,
+    )
+    .expect("reply construction should have succeeded");
+
+    let mut buffer = [0xff; 30];
+    let serialized_length = reply
+        .serialize_into_buffer(&mut buffer)
+        .expect("buffer should be large enough");
+
+    let mut expected = array_vec!([u8; 30]);
+    expected.extend_from_slice(&[
+        0x05, // status: getpass
+        1,    // noecho flag set
+        0, 10, // server message length
+        0, 2, // data length
+    ]);
+    expected.extend_from_slice(b"Password: "); // server message
+    expected.extend_from_slice(&[0xde, 0xad]); // data
+
+    assert_eq!(&buffer[..serialized_length], expected.as_slice());
+}
+
+#[test]
+fn serialize_reply_not_enough_space() {
+    let reply = Reply::new(
+        Status::Pass,
+        FieldText::assert("welcome!"),
+        &[],
+        ReplyFlags::empty(),
+    )
+    .unwrap();
+
+    let mut buffer = [0; 10];
+    assert_eq!(
+        reply.serialize_into_buffer(&mut buffer),
+        Err(SerializeError::NotEnoughSpace)
+    );
+}
+
+#[test]
+fn reply_data_too_long() {
+    let long_data = [0x2a; u16::MAX as usize + 1];
+
+    assert_eq!(
+        Reply::new(
+            Status::Fail,
+            FieldText::default(),
+            &long_data,
+            ReplyFlags::empty()
+        ),
+        None
+    );
+}
+
+#[test]
+fn serialize_obfuscated_reply_round_trip() {
+    let header = HeaderInfo::new(
+        Version::new(MajorVersion::RFC8907, MinorVersion::Default),
+        2,
+        PacketFlags::SINGLE_CONNECTION,
+        38204921,
+    );
+
+    let body = Reply::new(
+        Status::GetUser,
+        FieldText::assert("Username: "),
+        &[],
+        ReplyFlags::empty(),
+    )
+    .unwrap();
+
+    let packet = Packet::new(header, body.clone());
+
+    let secret_key = b"reply round trip key";
+    let mut buffer = [0; 40];
+    let serialized_length = packet
+        .serialize(secret_key, &mut buffer)
+        .expect("packet serialization should have succeeded");
+
+    // body length: required fields + server message
+    assert_eq!(serialized_length, HeaderInfo::HEADER_SIZE_BYTES + 6 + 10);
+
+    let deserialized: Packet<Reply> =
+        Packet::deserialize(secret_key, &mut buffer[..serialized_length])
+            .expect("packet deserialization should have succeeded");
+
+    assert_eq!(deserialized.header(), &header);
+    assert_eq!(deserialized.body(), &body);
+}
+
 #[test]
 fn serialize_continue_no_data() {
     let continue_body = Continue::new(None, None, ContinueFlags::empty())
@@ -505,3 +814,113 @@ fn serialize_continue_full_packet() {
 
     assert_eq!(&buffer[..serialized_length], expected.as_slice());
 }
+
+#[test]
+fn deserialize_continue_both_fields() {
+    let mut raw_body = array_vec!([u8; 30]);
+    raw_body.extend_from_slice(&[
+        0, 8, // user message length
+        0, 3, // data length
+        0, // no flags set
+    ]);
+    raw_body.extend_from_slice(b"password"); // user message
+    raw_body.extend_from_slice(&[0xa, 0xb, 0xc]); // data
+
+    assert_eq!(
+        Continue::deserialize_from_buffer(&raw_body),
+        Ok(Continue::new(
+            Some(b"password"),
+            Some(&[0xa, 0xb, 0xc]),
+            ContinueFlags::empty()
+        )
+        .unwrap())
+    );
+}
+
+#[test]
+fn deserialize_continue_abort_no_fields() {
+    let raw_body = [
+        0, 0, // user message length
+        0, 0, // data length
+        1, // abort flag set
+    ];
+
+    let parsed = Continue::deserialize_from_buffer(&raw_body)
+        .expect("continue deserialization should have succeeded");
+
+    assert_eq!(parsed.user_message(), None);
+    assert_eq!(parsed.data(), None);
+    assert_eq!(parsed.flags(), ContinueFlags::ABORT);
+}
+
+#[test]
+fn deserialize_continue_bad_flags() {
+    let raw_body = [
+        0, 1, // user message length
+        0, 0,    // data length
+        0x80, // invalid flags
+        b'a', // user message
+    ];
+
+    assert_eq!(
+        Continue::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidBodyFlags(0x80))
+    );
+}
+
+#[test]
+fn deserialize_continue_wrong_length() {
+    let raw_body = [
+        0, 4, // user message length
+        0, 1, // data length
+        0, // no flags set
+        b'u', b's', b'e', b'r', // user message (data missing)
+    ];
+
+    assert_eq!(
+        Continue::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::WrongBodyBufferSize {
+            expected: 10,
+            buffer_size: 9
+        })
+    );
+}
+
+#[test]
+fn deserialize_full_continue_packet() {
+    let session_id: u32 = 4123;
+    let mut raw_packet = array_vec!([u8; 40]);
+
+    // HEADER
+    raw_packet.extend_from_slice(&[
+        0xc << 4, // version (default minor version, for ASCII)
+        1,        // authentication packet
+        3,        // sequence number
+        1,        // unencrypted flag set
+    ]);
+    raw_packet.extend_from_slice(session_id.to_be_bytes().as_slice());
+    raw_packet.extend_from_slice(12_u32.to_be_bytes().as_slice()); // body length
+
+    // BODY
+    raw_packet.extend_from_slice(&[
+        0, 7, // user message length
+        0, 0, // data length
+        0, // no flags set
+    ]);
+    raw_packet.extend_from_slice(b"someone"); // user message
+
+    let packet: Packet<Continue> = Packet::deserialize_unobfuscated(&raw_packet)
+        .expect("packet deserialization should have succeeded");
+
+    assert_eq!(
+        packet.header(),
+        &HeaderInfo::new(
+            Version::new(MajorVersion::RFC8907, MinorVersion::Default),
+            3,
+            PacketFlags::UNENCRYPTED,
+            session_id
+        )
+    );
+    assert_eq!(packet.body().user_message(), Some(b"someone".as_slice()));
+    assert_eq!(packet.body().data(), None);
+}
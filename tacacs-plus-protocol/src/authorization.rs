@@ -3,41 +3,54 @@
 use core::fmt;
 
 use byteorder::{ByteOrder, NetworkEndian};
-use getset::Getters;
+use getset::{CopyGetters, Getters};
 use num_enum::{TryFromPrimitive, TryFromPrimitiveError};
 
 use super::{
-    Argument, Arguments, AuthenticationContext, AuthenticationMethod, DeserializeError,
-    InvalidArgument, PacketBody, PacketType, Serialize, SerializeError, UserInformation,
+    Arguments, AuthenticationContext, AuthenticationMethod, DeserializeError, PacketBody,
+    PacketType, Serialize, SerializeError, UserInformation,
 };
+use crate::arguments::{ArgumentsInfo, PacketArguments};
 use crate::{Deserialize, FieldText};
 
+// kept here as well for backwards compatibility, since this is where it was originally defined
+pub use crate::ArgumentsIterator;
+
 #[cfg(test)]
 mod tests;
 
 #[cfg(feature = "std")]
 mod owned;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+
 #[cfg(feature = "std")]
-pub use owned::ReplyOwned;
+pub use owned::{ReplyOwned, RequestOwned};
 
 /// An authorization request packet body, including arguments.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, CopyGetters)]
 pub struct Request<'packet> {
     /// Method used to authenticate to TACACS+ client.
+    #[getset(get_copy = "pub")]
     method: AuthenticationMethod,
 
     /// Other client authentication information.
+    #[getset(get_copy = "pub")]
     authentication_context: AuthenticationContext,
 
     /// Information about the user connected to the TACACS+ client.
+    #[getset(get = "pub")]
     user_information: UserInformation<'packet>,
 
     /// Additional arguments to provide as part of an authorization request.
-    arguments: Arguments<'packet>,
+    arguments: PacketArguments<'packet>,
 }
 
 impl<'packet> Request<'packet> {
+    /// Argument lengths in a request packet start at index 8, if present.
+    const ARGUMENT_LENGTHS_OFFSET: usize = 8;
+
     /// Assembles an authorization request packet from its fields.
     pub fn new(
         method: AuthenticationMethod,
@@ -49,7 +62,42 @@ impl<'packet> Request<'packet> {
             method,
             authentication_context,
             user_information,
-            arguments,
+            arguments: PacketArguments::Provided(arguments),
+        }
+    }
+
+    /// Returns an iterator over the arguments included in this request packet.
+    pub fn iter_arguments(&self) -> ArgumentsIterator<'_> {
+        self.arguments.iter()
+    }
+
+    /// Determines the length of a request packet based on encoded lengths at the beginning of the packet body, if possible.
+    pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
+        // argument count is the last required field
+        if buffer.len() >= Self::REQUIRED_FIELDS_LENGTH {
+            let argument_count = buffer[7] as usize;
+            let argument_lengths_end = Self::ARGUMENT_LENGTHS_OFFSET + argument_count;
+
+            // also ensure that all argument lengths are present
+            if buffer.len() >= argument_lengths_end {
+                let user_information_length =
+                    UserInformation::extract_values_length(&buffer[4..7])?;
+                let encoded_arguments_length: usize = buffer
+                    [Self::ARGUMENT_LENGTHS_OFFSET..argument_lengths_end]
+                    .iter()
+                    .map(|&length| length as usize)
+                    .sum();
+
+                // SAFETY: the sum of at most 256 single-byte lengths & a few constants is guaranteed to fit in a u32
+                Ok(u32::try_from(
+                    argument_lengths_end + user_information_length + encoded_arguments_length,
+                )
+                .unwrap())
+            } else {
+                Err(DeserializeError::UnexpectedEnd)
+            }
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
         }
     }
 }
@@ -79,7 +127,7 @@ impl Serialize for Request<'_> {
             self.user_information
                 .serialize_field_lengths(&mut buffer[4..7])?;
 
-            let argument_count = self.arguments.argument_count() as usize;
+            let argument_count = self.arguments.argument_count();
 
             // the user information fields start after all of the required fields and also the argument lengths, the latter of which take up 1 byte each
             let user_info_start = Self::REQUIRED_FIELDS_LENGTH + argument_count;
@@ -115,6 +163,46 @@ impl Serialize for Request<'_> {
     }
 }
 
+impl<'raw> Deserialize<'raw> for Request<'raw> {
+    fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
+        let total_length = Self::extract_total_length(buffer)? as usize;
+
+        // buffer argument is sliced to proper length in Packet::deserialize_body(), so we can compare against that header length indirectly like this
+        let length_from_header = buffer.len();
+
+        if total_length == length_from_header {
+            let method = AuthenticationMethod::try_from(buffer[0])?;
+            let authentication_context = AuthenticationContext::deserialize(&buffer[1..4])?;
+
+            // user information values come after the argument lengths
+            let argument_count = buffer[7] as usize;
+            let user_information_start = Self::ARGUMENT_LENGTHS_OFFSET + argument_count;
+            let user_information =
+                UserInformation::deserialize(&buffer[4..7], &buffer[user_information_start..])?;
+
+            // arguments occupy the rest of the buffer
+            let arguments_start =
+                user_information_start + UserInformation::extract_values_length(&buffer[4..7])?;
+            let arguments_info = ArgumentsInfo::new(
+                &buffer[Self::ARGUMENT_LENGTHS_OFFSET..user_information_start],
+                &buffer[arguments_start..total_length],
+            )?;
+
+            Ok(Self {
+                method,
+                authentication_context,
+                user_information,
+                arguments: PacketArguments::Raw(arguments_info),
+            })
+        } else {
+            Err(DeserializeError::WrongBodyBufferSize {
+                expected: total_length,
+                buffer_size: length_from_header,
+            })
+        }
+    }
+}
+
 /// The status of an authorization operation, as returned by the server.
 #[repr(u8)]
 #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, TryFromPrimitive)]
@@ -166,14 +254,6 @@ impl From<TryFromPrimitiveError<Status>> for DeserializeError {
     }
 }
 
-/// Information about a reply packet's arguments.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct ArgumentsInfo<'raw> {
-    argument_count: u8,
-    argument_lengths: &'raw [u8],
-    arguments_buffer: &'raw [u8],
-}
-
 /// The body of an authorization reply packet.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Getters)]
 pub struct Reply<'packet> {
@@ -191,7 +271,7 @@ pub struct Reply<'packet> {
 
     // this field not publicly exposed on purpose
     // (used for iterating over arguments)
-    arguments_info: ArgumentsInfo<'packet>,
+    arguments: PacketArguments<'packet>,
 }
 
 /// The non-argument field lengths of a (raw) authorization reply packet, as well as its total length.
@@ -201,57 +281,31 @@ struct ReplyFieldLengths {
     total_length: u32,
 }
 
-/// An iterator over the arguments in an authorization reply packet.
-#[derive(Debug, Clone)]
-pub struct ArgumentsIterator<'iter> {
-    /// Argument information, including argument count.
-    arguments_info: &'iter ArgumentsInfo<'iter>,
-
-    /// Position of the next argument, as if into a zero-indexed array of complete arguments.
-    next_argument_number: usize,
-
-    /// Offset of an argument within the buffer.
-    next_offset: usize,
-}
-
-impl<'iter> Iterator for ArgumentsIterator<'iter> {
-    type Item = Argument<'iter>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next_argument_number < self.arguments_info.argument_count as usize {
-            // get encoded argument from buffer based on stored offset into buffer/length
-            let next_length =
-                self.arguments_info.argument_lengths[self.next_argument_number] as usize;
-            let raw_argument = &self.arguments_info.arguments_buffer
-                [self.next_offset..self.next_offset + next_length];
-
-            // update iterator state
-            self.next_argument_number += 1;
-            self.next_offset += next_length;
+impl<'packet> Reply<'packet> {
+    const ARGUMENT_LENGTHS_START: usize = 6;
 
-            // NOTE: this should always be Some, since the validity of arguments is checked in Reply's TryFrom impl
-            Argument::deserialize(raw_argument).ok()
+    /// Assembles an authorization reply packet from its fields, performing length checks on the server message
+    /// and data fields to ensure encodable lengths.
+    ///
+    /// The argument count is already limited to `u8::MAX` by the [`Arguments`] constructor.
+    pub fn new(
+        status: Status,
+        arguments: Arguments<'packet>,
+        server_message: FieldText<'packet>,
+        data: FieldText<'packet>,
+    ) -> Option<Self> {
+        if u16::try_from(server_message.len()).is_ok() && u16::try_from(data.len()).is_ok() {
+            Some(Self {
+                status,
+                server_message,
+                data,
+                arguments: PacketArguments::Provided(arguments),
+            })
         } else {
             None
         }
     }
 
-    // required for ExactSizeIterator impl
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let total_size = self.arguments_info.argument_count as usize;
-        let remaining_size = total_size - self.next_argument_number;
-
-        // these are asserted to be equal in the default ExactSizeIterator::len() implementation
-        (remaining_size, Some(remaining_size))
-    }
-}
-
-// Gives ArgumentsIterator a .len() method
-impl ExactSizeIterator for ArgumentsIterator<'_> {}
-
-impl<'packet> Reply<'packet> {
-    const ARGUMENT_LENGTHS_START: usize = 6;
-
     /// Determines the length of a reply packet based on encoded lengths at the beginning of the packet body, if possible.
     pub fn extract_total_length(buffer: &[u8]) -> Result<u32, DeserializeError> {
         Self::extract_field_lengths(buffer).map(|lengths| lengths.total_length)
@@ -294,26 +348,9 @@ impl<'packet> Reply<'packet> {
         }
     }
 
-    /// Ensures a list of argument lengths and their raw values represent a valid set of arguments.
-    fn ensure_arguments_valid(lengths: &[u8], values: &[u8]) -> Result<(), InvalidArgument> {
-        let mut argument_start = 0;
-
-        lengths.iter().try_fold((), |_, &length| {
-            let raw_argument = &values[argument_start..argument_start + length as usize];
-            argument_start += length as usize;
-
-            // we don't care about the actual argument here, but the specific error should be kept
-            Argument::deserialize(raw_argument).map(|_| ())
-        })
-    }
-
     /// Returns an iterator over the arguments included in this reply packet.
     pub fn iter_arguments(&self) -> ArgumentsIterator<'_> {
-        ArgumentsIterator {
-            arguments_info: &self.arguments_info,
-            next_argument_number: 0,
-            next_offset: 0,
-        }
+        self.arguments.iter()
     }
 }
 
@@ -324,6 +361,67 @@ impl PacketBody for Reply<'_> {
     const REQUIRED_FIELDS_LENGTH: usize = Status::WIRE_SIZE + 1 + 4;
 }
 
+impl Serialize for Reply<'_> {
+    fn wire_size(&self) -> usize {
+        Status::WIRE_SIZE
+            + 4 // server message & data lengths
+            + self.server_message.len()
+            + self.data.len()
+            + self.arguments.wire_size()
+    }
+
+    fn serialize_into_buffer(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        let wire_size = self.wire_size();
+
+        if buffer.len() >= wire_size {
+            buffer[0] = self.status as u8;
+
+            // the argument count & lengths aren't contiguous in a reply body, so they're serialized
+            // such that the lengths land in the right spot and the count is then moved to index 1
+            let argument_count_index = Self::ARGUMENT_LENGTHS_START - 1;
+            let count_and_lengths_len = self
+                .arguments
+                .serialize_count_and_lengths(&mut buffer[argument_count_index..wire_size])?;
+            buffer[1] = buffer[argument_count_index];
+
+            // field lengths are written after the argument count was moved, since the data length
+            // overlaps the temporary argument count position
+            // (lengths are also verified to fit in a u16 in new(), but verify anyways)
+            let server_message_len = self.server_message.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[2..4], server_message_len);
+
+            let data_len = self.data.len().try_into()?;
+            NetworkEndian::write_u16(&mut buffer[4..6], data_len);
+
+            // server message starts after the argument lengths
+            let server_message_start = argument_count_index + count_and_lengths_len;
+            let data_start = server_message_start + server_message_len as usize;
+            let arguments_start = data_start + data_len as usize;
+
+            buffer[server_message_start..data_start]
+                .copy_from_slice(self.server_message.as_bytes());
+            buffer[data_start..arguments_start].copy_from_slice(self.data.as_bytes());
+
+            let argument_values_len = self
+                .arguments
+                .serialize_encoded_values(&mut buffer[arguments_start..wire_size])?;
+
+            let actual_written_len = arguments_start + argument_values_len;
+
+            if actual_written_len == wire_size {
+                Ok(actual_written_len)
+            } else {
+                Err(SerializeError::LengthMismatch {
+                    expected: wire_size,
+                    actual: actual_written_len,
+                })
+            }
+        } else {
+            Err(SerializeError::NotEnoughSpace)
+        }
+    }
+}
+
 impl<'raw> Deserialize<'raw> for Reply<'raw> {
     fn deserialize_from_buffer(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
         let ReplyFieldLengths {
@@ -353,20 +451,14 @@ impl<'raw> Deserialize<'raw> for Reply<'raw> {
             let argument_lengths = &buffer[Self::ARGUMENT_LENGTHS_START..body_start];
             let argument_values = &buffer[arguments_start..total_length as usize];
 
-            Self::ensure_arguments_valid(argument_lengths, argument_values)?;
-
-            // bundle some information about arguments for iterator purposes
-            let arguments_info = ArgumentsInfo {
-                argument_count,
-                argument_lengths,
-                arguments_buffer: argument_values,
-            };
+            // bundle some information about arguments for iterator purposes, also ensuring they're valid
+            let arguments_info = ArgumentsInfo::new(argument_lengths, argument_values)?;
 
             Ok(Self {
                 status,
                 server_message,
                 data,
-                arguments_info,
+                arguments: PacketArguments::Raw(arguments_info),
             })
         } else {
             Err(DeserializeError::WrongBodyBufferSize {
@@ -0,0 +1,49 @@
+use arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+use super::{Reply, Request, Status};
+
+impl<'a> Arbitrary<'a> for Status {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        #[allow(deprecated)]
+        let statuses = [
+            Self::PassAdd,
+            Self::PassReplace,
+            Self::Fail,
+            Self::Error,
+            Self::Follow,
+        ];
+
+        u.choose(&statuses).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Request<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            method: u.arbitrary()?,
+            authentication_context: u.arbitrary()?,
+            user_information: u.arbitrary()?,
+            arguments: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Reply<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let reply = Self {
+            status: u.arbitrary()?,
+            server_message: u.arbitrary()?,
+            data: u.arbitrary()?,
+            arguments: u.arbitrary()?,
+        };
+
+        // perform the same length checks as Reply::new()
+        if u16::try_from(reply.server_message.len()).is_ok()
+            && u16::try_from(reply.data.len()).is_ok()
+        {
+            Ok(reply)
+        } else {
+            Err(Error::IncorrectFormat)
+        }
+    }
+}
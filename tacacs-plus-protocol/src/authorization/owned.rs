@@ -1,10 +1,54 @@
 use std::string::{String, ToString};
 use std::vec::Vec;
 
-use super::{Reply, Status};
+use super::{Reply, Request, Status};
 use crate::owned::FromBorrowedBody;
 use crate::sealed::Sealed;
-use crate::Argument;
+use crate::{Argument, AuthenticationContext, AuthenticationMethod};
+
+/// An authorization request packet with owned fields.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestOwned {
+    /// The method used to authenticate to the TACACS+ client.
+    pub method: AuthenticationMethod,
+
+    /// Other information about the authentication of the user.
+    pub authentication_context: AuthenticationContext,
+
+    /// The user connected to the client.
+    pub user: String,
+
+    /// The port the user is connected to.
+    pub port: String,
+
+    /// The remote address the user is connecting from.
+    pub remote_address: String,
+
+    /// The arguments sent by the client.
+    pub arguments: Vec<Argument<'static>>,
+}
+
+impl Sealed for RequestOwned {}
+
+impl FromBorrowedBody for RequestOwned {
+    type Borrowed<'b> = Request<'b>;
+
+    fn from_borrowed(borrowed: &Self::Borrowed<'_>) -> Self {
+        let arguments_vec = borrowed
+            .iter_arguments()
+            .map(Argument::into_owned)
+            .collect();
+
+        RequestOwned {
+            method: borrowed.method,
+            authentication_context: borrowed.authentication_context,
+            user: borrowed.user_information.user().to_string(),
+            port: borrowed.user_information.port().to_string(),
+            remote_address: borrowed.user_information.remote_address().to_string(),
+            arguments: arguments_vec,
+        }
+    }
+}
 
 /// An authorization reply packet with owned fields.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
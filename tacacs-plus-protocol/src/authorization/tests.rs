@@ -1,10 +1,11 @@
 use super::*;
+use crate::arguments::PacketArguments;
 use crate::packet::xor_body_with_pad;
 use crate::FieldText;
 use crate::{
-    Arguments, AuthenticationContext, AuthenticationMethod, AuthenticationService,
-    AuthenticationType, HeaderInfo, MajorVersion, MinorVersion, Packet, PacketFlags,
-    PrivilegeLevel, Serialize, UserInformation, Version,
+    Argument, Arguments, AuthenticationContext, AuthenticationMethod, AuthenticationService,
+    AuthenticationType, HeaderInfo, InvalidArgument, MajorVersion, MinorVersion, Packet,
+    PacketFlags, PrivilegeLevel, Serialize, UserInformation, Version,
 };
 
 use tinyvec::array_vec;
@@ -28,7 +29,7 @@ fn serialize_request_no_arguments() {
         method: AuthenticationMethod::Enable,
         authentication_context,
         user_information,
-        arguments: Arguments::new(&[]).unwrap(),
+        arguments: PacketArguments::Provided(Arguments::new(&[]).unwrap()),
     };
 
     let mut buffer = [0u8; 40];
@@ -83,7 +84,7 @@ fn serialize_request_one_argument() {
         method: AuthenticationMethod::TacacsPlus,
         authentication_context,
         user_information,
-        arguments,
+        arguments: PacketArguments::Provided(arguments),
     };
 
     let mut buffer = [0u8; 60];
@@ -147,7 +148,7 @@ fn serialize_full_request_packet() {
             FieldText::assert("127.254.1.2"),
         )
         .unwrap(),
-        arguments: Arguments::new(&arguments).unwrap(),
+        arguments: PacketArguments::Provided(Arguments::new(&arguments).unwrap()),
     };
 
     let packet = Packet::new(header, body);
@@ -194,6 +195,175 @@ fn serialize_full_request_packet() {
     assert_eq!(&buffer[..serialized_length], expected.as_slice());
 }
 
+#[test]
+fn deserialize_request_two_arguments() {
+    let mut raw_body = array_vec!([u8; 70]);
+    raw_body.extend_from_slice(&[
+        0x06, // authentication method: TACACS+
+        3,    // privilege level
+        0x02, // authentication type: PAP
+        0x03, // authentication service: PPP
+        5,    // user length
+        4,    // port length
+        9,    // remote address length
+        2,    // argument count
+        11,   // argument 1 length
+        12,   // argument 2 length
+    ]);
+
+    // user information
+    raw_body.extend_from_slice(b"admin");
+    raw_body.extend_from_slice(b"tty2");
+    raw_body.extend_from_slice(b"127.0.0.1");
+
+    // arguments
+    raw_body.extend_from_slice(b"service=ppp");
+    raw_body.extend_from_slice(b"protocol*lcp");
+
+    let parsed = Request::deserialize_from_buffer(&raw_body)
+        .expect("request deserialization should have succeeded");
+
+    assert_eq!(parsed.method(), AuthenticationMethod::TacacsPlus);
+    assert_eq!(
+        parsed.authentication_context(),
+        AuthenticationContext {
+            privilege_level: PrivilegeLevel::new(3).unwrap(),
+            authentication_type: AuthenticationType::Pap,
+            service: AuthenticationService::Ppp,
+        }
+    );
+    assert_eq!(
+        parsed.user_information(),
+        &UserInformation::new(
+            "admin",
+            FieldText::assert("tty2"),
+            FieldText::assert("127.0.0.1")
+        )
+        .unwrap()
+    );
+
+    let mut arguments_iter = parsed.iter_arguments();
+    assert_eq!(arguments_iter.len(), 2);
+    assert_eq!(
+        arguments_iter.next(),
+        Some(Argument::new(FieldText::assert("service"), FieldText::assert("ppp"), true).unwrap())
+    );
+    assert_eq!(
+        arguments_iter.next(),
+        Some(
+            Argument::new(
+                FieldText::assert("protocol"),
+                FieldText::assert("lcp"),
+                false
+            )
+            .unwrap()
+        )
+    );
+    assert_eq!(arguments_iter.next(), None);
+}
+
+#[test]
+fn deserialize_request_equals_constructed() {
+    let arguments_list =
+        [Argument::new(FieldText::assert("cmd"), FieldText::assert("show"), true).unwrap()];
+
+    let request = Request::new(
+        AuthenticationMethod::Local,
+        AuthenticationContext {
+            privilege_level: PrivilegeLevel::new(15).unwrap(),
+            authentication_type: AuthenticationType::NotSet,
+            service: AuthenticationService::Login,
+        },
+        UserInformation::new(
+            "operator",
+            FieldText::assert("vty0"),
+            FieldText::assert("192.168.1.1"),
+        )
+        .unwrap(),
+        Arguments::new(&arguments_list).unwrap(),
+    );
+
+    let mut buffer = [0u8; 60];
+    let serialized_length = request
+        .serialize_into_buffer(&mut buffer)
+        .expect("request serialization should have succeeded");
+
+    let parsed = Request::deserialize_from_buffer(&buffer[..serialized_length])
+        .expect("request deserialization should have succeeded");
+    assert_eq!(parsed, request);
+
+    // deserialized requests should also be able to be serialized again, e.g. by a proxy
+    let mut reserialized = [0u8; 60];
+    let reserialized_length = parsed
+        .serialize_into_buffer(&mut reserialized)
+        .expect("deserialized request should be serializable");
+    assert_eq!(
+        &reserialized[..reserialized_length],
+        &buffer[..serialized_length]
+    );
+}
+
+#[test]
+fn deserialize_request_bad_argument() {
+    let raw_body = [
+        0x01, // authentication method: none
+        0,    // privilege level
+        0x00, // authentication type: not set
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        1,    // argument count
+        4,    // argument 1 length
+        b'n', b'a', b'm', b'e', // argument with no delimiter
+    ];
+
+    assert_eq!(
+        Request::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidArgument(
+            InvalidArgument::NoDelimiter
+        ))
+    );
+}
+
+#[test]
+fn deserialize_request_bad_method() {
+    let raw_body = [
+        0x07, // invalid authentication method
+        0,    // privilege level
+        0x00, // authentication type: not set
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        0,    // argument count
+    ];
+
+    assert_eq!(
+        Request::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::InvalidAuthenticationMethod(0x07))
+    );
+}
+
+#[test]
+fn deserialize_request_missing_argument_lengths() {
+    let raw_body = [
+        0x01, // authentication method: none
+        0,    // privilege level
+        0x00, // authentication type: not set
+        0x01, // authentication service: login
+        0,    // user length
+        0,    // port length
+        0,    // remote address length
+        3,    // argument count (but no lengths follow)
+    ];
+
+    assert_eq!(
+        Request::deserialize_from_buffer(&raw_body),
+        Err(DeserializeError::UnexpectedEnd)
+    );
+}
+
 #[test]
 fn deserialize_reply_no_arguments() {
     let mut raw_bytes = array_vec!([u8; 50]);
@@ -506,3 +676,133 @@ fn full_unobfuscated_reply_packet_to_owned() {
         .unwrap()]
     );
 }
+
+#[test]
+fn serialize_reply_two_arguments() {
+    let arguments_list = [
+        Argument::new(
+            FieldText::assert("service"),
+            FieldText::assert("greet"),
+            true,
+        )
+        .unwrap(),
+        Argument::new(
+            FieldText::assert("person"),
+            FieldText::assert("world!"),
+            false,
+        )
+        .unwrap(),
+    ];
+    let arguments = Arguments::new(&arguments_list).expect("argument list should be valid");
+
+    let reply = Reply::new(
+        Status::PassReplace,
+        arguments,
+        FieldText::assert("hello"),
+        FieldText::assert("world"),
+    )
+    .expect("reply fields should be valid");
+
+    let mut buffer = [0xff; 50];
+    let serialized_length = reply
+        .serialize_into_buffer(&mut buffer)
+        .expect("reply serialization should have succeeded");
+
+    let mut expected = array_vec!([u8; 50]);
+    expected.extend_from_slice(&[
+        0x02, // status: pass/replace
+        2,    // two arguments
+        0, 5, // server message length
+        0, 5,  // data length
+        13, // argument 1 length
+        13, // argument 2 length
+    ]);
+    expected.extend_from_slice(b"hello");
+    expected.extend_from_slice(b"world");
+    expected.extend_from_slice(b"service=greet");
+    expected.extend_from_slice(b"person*world!");
+
+    assert_eq!(serialized_length, expected.len());
+    assert_eq!(&buffer[..serialized_length], expected.as_slice());
+
+    // the serialized reply should also deserialize back to the original
+    let deserialized = Reply::deserialize_from_buffer(&buffer[..serialized_length])
+        .expect("deserialization of serialized reply should have succeeded");
+    assert_eq!(deserialized, reply);
+}
+
+#[test]
+fn serialize_reply_not_enough_space() {
+    let arguments_list = [Argument::new(
+        FieldText::assert("service"),
+        FieldText::assert("tight"),
+        true,
+    )
+    .unwrap()];
+    let arguments = Arguments::new(&arguments_list).expect("argument list should be valid");
+
+    let reply = Reply::new(
+        Status::PassAdd,
+        arguments,
+        FieldText::assert("message"),
+        FieldText::assert(""),
+    )
+    .expect("reply fields should be valid");
+
+    let mut buffer = [0; 20];
+    assert_eq!(
+        reply.serialize_into_buffer(&mut buffer),
+        Err(SerializeError::NotEnoughSpace)
+    );
+}
+
+#[test]
+fn reply_server_message_too_long() {
+    let long_message = [b'a'; u16::MAX as usize + 1];
+    let long_message_text =
+        FieldText::try_from(core::str::from_utf8(&long_message).unwrap()).unwrap();
+
+    let reply = Reply::new(
+        Status::Fail,
+        Arguments::new(&[]).unwrap(),
+        long_message_text,
+        FieldText::assert(""),
+    );
+    assert!(
+        reply.is_none(),
+        "overly long server message should be rejected"
+    );
+}
+
+#[test]
+fn serialize_full_reply_packet() {
+    let arguments_list =
+        [Argument::new(FieldText::assert("priv-lvl"), FieldText::assert("15"), true).unwrap()];
+    let arguments = Arguments::new(&arguments_list).expect("argument list should be valid");
+
+    let packet = Packet::new(
+        HeaderInfo::new(
+            Version::new(MajorVersion::RFC8907, MinorVersion::Default),
+            2,
+            PacketFlags::UNENCRYPTED,
+            12345,
+        ),
+        Reply::new(
+            Status::PassAdd,
+            arguments,
+            FieldText::assert(""),
+            FieldText::assert(""),
+        )
+        .expect("reply fields should be valid"),
+    );
+
+    let mut buffer = [0; 40];
+    let packet_length = packet
+        .clone()
+        .serialize_unobfuscated(&mut buffer)
+        .expect("packet serialization should have succeeded");
+
+    let parsed: Packet<Reply> = Packet::deserialize_unobfuscated(&buffer[..packet_length])
+        .expect("serialized packet should deserialize");
+    assert_eq!(parsed, packet);
+}
@@ -0,0 +1,228 @@
+//! Incremental, sans-IO framing of raw packets from a byte stream.
+
+use core::fmt;
+
+use byteorder::{ByteOrder, NetworkEndian};
+
+use crate::HeaderInfo;
+
+#[cfg(test)]
+mod tests;
+
+/// Storage that a [`PacketDecoder`] accumulates a frame in.
+///
+/// This is implemented for fixed-size arrays and mutable slices, as well as for `Vec<u8>`
+/// (which grows as necessary) if the `std` feature is enabled.
+pub trait FrameBuffer {
+    /// Returns a slice of exactly `length` bytes to store a frame in, or `None` if the buffer can't hold that many.
+    ///
+    /// Any bytes previously written to the buffer must be preserved.
+    fn with_length(&mut self, length: usize) -> Option<&mut [u8]>;
+}
+
+impl<const N: usize> FrameBuffer for [u8; N] {
+    fn with_length(&mut self, length: usize) -> Option<&mut [u8]> {
+        self.get_mut(..length)
+    }
+}
+
+impl FrameBuffer for &mut [u8] {
+    fn with_length(&mut self, length: usize) -> Option<&mut [u8]> {
+        self.get_mut(..length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FrameBuffer for std::vec::Vec<u8> {
+    fn with_length(&mut self, length: usize) -> Option<&mut [u8]> {
+        if self.len() < length {
+            self.resize(length, 0);
+        }
+
+        Some(&mut self[..length])
+    }
+}
+
+/// An error encountered while accumulating a frame in a [`PacketDecoder`].
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// The body length reported in a packet header exceeded the configured maximum.
+    BodyTooLong {
+        /// The body length reported in the packet header.
+        length: u32,
+
+        /// The maximum body length accepted by the decoder.
+        maximum: usize,
+    },
+
+    /// The decoder's buffer couldn't hold a full frame.
+    BufferTooSmall {
+        /// The number of bytes required to hold the frame.
+        required: usize,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BodyTooLong { length, maximum } => write!(
+                f,
+                "packet body length {length} exceeded maximum of {maximum} bytes"
+            ),
+            Self::BufferTooSmall { required } => {
+                write!(f, "frame buffer couldn't hold {required} bytes")
+            }
+        }
+    }
+}
+
+/// A sans-IO decoder that splits a stream of bytes into complete raw packets ("frames").
+///
+/// Bytes can be provided in arbitrary chunks via [`feed()`](Self::feed); once a full frame has been
+/// accumulated, it can be obtained with [`take_frame()`](Self::take_frame) and then deserialized with
+/// e.g. [`Packet::deserialize()`](crate::Packet::deserialize).
+///
+/// # Examples
+///
+/// ```
+/// use tacacs_plus_protocol::PacketDecoder;
+///
+/// // a header for an (obfuscated) accounting reply packet with a 5-byte body
+/// let header = [0xc0, 3, 2, 0, 0, 0, 0, 1, 0, 0, 0, 5];
+///
+/// let mut decoder = PacketDecoder::new([0; 64]);
+/// assert_eq!(decoder.bytes_needed(), 12);
+///
+/// // feed the header in two chunks
+/// decoder.feed(&header[..4]).unwrap();
+/// decoder.feed(&header[4..]).unwrap();
+/// assert_eq!(decoder.bytes_needed(), 5);
+///
+/// // extra bytes past the end of a frame are left unconsumed
+/// assert_eq!(decoder.feed(&[0; 8]).unwrap(), 5);
+///
+/// let frame = decoder.take_frame().expect("frame should be complete");
+/// assert_eq!(frame.len(), 17);
+/// ```
+#[derive(Debug)]
+pub struct PacketDecoder<T> {
+    /// Storage for the frame currently being accumulated.
+    buffer: T,
+
+    /// The number of bytes of the current frame that have been stored.
+    filled: usize,
+
+    /// The full length of the current frame, known once its header has been received.
+    frame_length: Option<usize>,
+
+    /// The maximum body length accepted from packet headers.
+    max_body_length: usize,
+}
+
+impl<T: FrameBuffer> PacketDecoder<T> {
+    /// The default maximum body length, which is the size of the largest possible valid packet body.
+    ///
+    /// That packet is an authorization reply with 255 arguments, each 255 bytes long,
+    /// along with server message and data fields of the maximum length.
+    pub const DEFAULT_MAX_BODY_LENGTH: usize = 6 + 255 + 2 * u16::MAX as usize + 255 * 255;
+
+    /// Creates a decoder that accumulates frames in the provided buffer,
+    /// using [`DEFAULT_MAX_BODY_LENGTH`](Self::DEFAULT_MAX_BODY_LENGTH) as the maximum body length.
+    pub fn new(buffer: T) -> Self {
+        Self::with_max_body_length(buffer, Self::DEFAULT_MAX_BODY_LENGTH)
+    }
+
+    /// Creates a decoder that accumulates frames in the provided buffer and rejects packets
+    /// with bodies longer than `max_body_length`.
+    pub fn with_max_body_length(buffer: T, max_body_length: usize) -> Self {
+        Self {
+            buffer,
+            filled: 0,
+            frame_length: None,
+            max_body_length,
+        }
+    }
+
+    /// Returns the number of bytes needed to complete the current frame.
+    ///
+    /// Before the header of a frame has been received, this is just the number of bytes
+    /// remaining in the header, since the body length isn't known yet.
+    pub fn bytes_needed(&self) -> usize {
+        self.frame_length
+            .unwrap_or(HeaderInfo::HEADER_SIZE_BYTES)
+            .saturating_sub(self.filled)
+    }
+
+    /// Returns whether a complete frame has been accumulated.
+    pub fn is_complete(&self) -> bool {
+        self.frame_length.is_some() && self.bytes_needed() == 0
+    }
+
+    /// Feeds a chunk of bytes to the decoder, returning how many bytes were consumed.
+    ///
+    /// Bytes are only consumed up to the end of the current frame, so if fewer bytes than were
+    /// provided are consumed, the remainder should be fed again after taking the completed frame.
+    ///
+    /// If an error is returned, the decoder should be [`reset()`](Self::reset) before it is used again.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<usize, FrameError> {
+        let mut consumed = 0;
+
+        while consumed < chunk.len() && !self.is_complete() {
+            let copy_length = self.bytes_needed().min(chunk.len() - consumed);
+            let new_filled = self.filled + copy_length;
+
+            let storage =
+                self.buffer
+                    .with_length(new_filled)
+                    .ok_or(FrameError::BufferTooSmall {
+                        required: new_filled,
+                    })?;
+            storage[self.filled..new_filled]
+                .copy_from_slice(&chunk[consumed..consumed + copy_length]);
+
+            self.filled = new_filled;
+            consumed += copy_length;
+
+            // the full frame length can be determined once the header is fully received
+            if self.frame_length.is_none() && self.filled == HeaderInfo::HEADER_SIZE_BYTES {
+                // body length is stored at the end of the 12-byte header
+                let body_length = NetworkEndian::read_u32(&storage[8..12]);
+
+                if body_length as usize > self.max_body_length {
+                    return Err(FrameError::BodyTooLong {
+                        length: body_length,
+                        maximum: self.max_body_length,
+                    });
+                }
+
+                self.frame_length = Some(HeaderInfo::HEADER_SIZE_BYTES + body_length as usize);
+            }
+        }
+
+        Ok(consumed)
+    }
+
+    /// Returns the accumulated frame if it's complete, and prepares the decoder for the next frame.
+    pub fn take_frame(&mut self) -> Option<&mut [u8]> {
+        if self.is_complete() {
+            let frame_length = self.filled;
+            self.reset();
+
+            self.buffer.with_length(frame_length)
+        } else {
+            None
+        }
+    }
+
+    /// Discards any partially accumulated frame.
+    pub fn reset(&mut self) {
+        self.filled = 0;
+        self.frame_length = None;
+    }
+
+    /// Consumes the decoder, returning its underlying buffer.
+    pub fn into_inner(self) -> T {
+        self.buffer
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+
+/// Header of an accounting reply packet with the provided body length.
+fn header_with_body_length(body_length: u32) -> [u8; 12] {
+    let mut header = [
+        0xc << 4, // version
+        3,        // accounting packet
+        2,        // sequence number
+        0,        // no flags
+        // session id
+        0,
+        0,
+        0,
+        1,
+        // body length (filled in below)
+        0,
+        0,
+        0,
+        0,
+    ];
+    NetworkEndian::write_u32(&mut header[8..12], body_length);
+    header
+}
+
+#[test]
+fn decode_frame_byte_by_byte() {
+    let header = header_with_body_length(3);
+    let mut decoder = PacketDecoder::new([0; 32]);
+
+    for (index, byte) in header.iter().enumerate() {
+        assert_eq!(
+            decoder.bytes_needed(),
+            HeaderInfo::HEADER_SIZE_BYTES - index
+        );
+        assert_eq!(decoder.feed(&[*byte]), Ok(1));
+    }
+
+    // body length should be known once the header is received
+    assert_eq!(decoder.bytes_needed(), 3);
+    assert!(!decoder.is_complete());
+    assert_eq!(decoder.take_frame(), None);
+
+    for byte in [1, 2, 3] {
+        assert_eq!(decoder.feed(&[byte]), Ok(1));
+    }
+
+    assert!(decoder.is_complete());
+
+    let frame = decoder.take_frame().expect("frame should be complete");
+    assert_eq!(&frame[..12], header.as_slice());
+    assert_eq!(&frame[12..], [1, 2, 3].as_slice());
+
+    // decoder should be ready for another frame
+    assert_eq!(decoder.bytes_needed(), HeaderInfo::HEADER_SIZE_BYTES);
+}
+
+#[test]
+fn decode_multiple_frames_from_one_chunk() {
+    let mut stream = [0; 30];
+    stream[..12].copy_from_slice(&header_with_body_length(2));
+    stream[12..14].copy_from_slice(&[0xaa, 0xbb]);
+    stream[14..26].copy_from_slice(&header_with_body_length(4));
+    stream[26..30].copy_from_slice(&[1, 2, 3, 4]);
+
+    let mut decoder = PacketDecoder::new([0; 20]);
+
+    let consumed = decoder.feed(&stream).expect("feeding should succeed");
+    assert_eq!(consumed, 14);
+    assert_eq!(
+        &decoder
+            .take_frame()
+            .expect("first frame should be complete")[12..],
+        [0xaa, 0xbb].as_slice()
+    );
+
+    assert_eq!(decoder.feed(&stream[consumed..]), Ok(16));
+    assert_eq!(
+        &decoder
+            .take_frame()
+            .expect("second frame should be complete")[12..],
+        [1, 2, 3, 4].as_slice()
+    );
+}
+
+#[test]
+fn empty_body_completes_after_header() {
+    let mut decoder = PacketDecoder::new([0; 12]);
+    assert_eq!(decoder.feed(&header_with_body_length(0)), Ok(12));
+    assert!(decoder.is_complete());
+    assert_eq!(decoder.take_frame().map(|frame| frame.len()), Some(12));
+}
+
+#[test]
+fn body_longer_than_maximum() {
+    let mut decoder = PacketDecoder::with_max_body_length([0; 64], 10);
+
+    assert_eq!(
+        decoder.feed(&header_with_body_length(11)),
+        Err(FrameError::BodyTooLong {
+            length: 11,
+            maximum: 10
+        })
+    );
+}
+
+#[test]
+fn buffer_too_small_for_frame() {
+    let mut storage = [0; 14];
+    let mut decoder = PacketDecoder::new(storage.as_mut_slice());
+
+    assert_eq!(decoder.feed(&header_with_body_length(5)), Ok(12));
+    assert_eq!(
+        decoder.feed(&[0; 5]),
+        Err(FrameError::BufferTooSmall { required: 17 })
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn vec_buffer_grows_to_fit_frame() {
+    let mut decoder = PacketDecoder::new(std::vec::Vec::new());
+
+    decoder
+        .feed(&header_with_body_length(100))
+        .expect("header should be accepted");
+    decoder.feed(&[7; 100]).expect("body should be accepted");
+
+    let frame = decoder.take_frame().expect("frame should be complete");
+    assert_eq!(frame.len(), 112);
+    assert!(frame[12..].iter().all(|&byte| byte == 7));
+}
@@ -1,17 +1,19 @@
 use core::fmt;
 use getset::{CopyGetters, Getters};
+use num_enum::{TryFromPrimitive, TryFromPrimitiveError};
 
 use crate::FieldText;
 use crate::MinorVersion;
 
-use super::SerializeError;
+use super::{DeserializeError, SerializeError};
 
 #[cfg(test)]
 mod tests;
 
 /// The method used to authenticate to the TACACS+ client.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum AuthenticationMethod {
     /// Unknown.
     NotSet = 0x00,
@@ -52,6 +54,13 @@ impl AuthenticationMethod {
     pub(super) const WIRE_SIZE: usize = 1;
 }
 
+#[doc(hidden)]
+impl From<TryFromPrimitiveError<AuthenticationMethod>> for DeserializeError {
+    fn from(value: TryFromPrimitiveError<AuthenticationMethod>) -> Self {
+        Self::InvalidAuthenticationMethod(value.number)
+    }
+}
+
 impl fmt::Display for AuthenticationMethod {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -122,7 +131,8 @@ impl fmt::Display for PrivilegeLevel {
 ///
 /// [RFC-8907 Section 10.1]: https://datatracker.ietf.org/doc/html/rfc8907#section-10.1.
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum AuthenticationType {
     /// Authentication type not set, typically when it's not available to the client.
     ///
@@ -156,6 +166,13 @@ impl AuthenticationType {
     }
 }
 
+#[doc(hidden)]
+impl From<TryFromPrimitiveError<AuthenticationType>> for DeserializeError {
+    fn from(value: TryFromPrimitiveError<AuthenticationType>) -> Self {
+        Self::InvalidAuthenticationType(value.number)
+    }
+}
+
 impl fmt::Display for AuthenticationType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -175,7 +192,8 @@ impl fmt::Display for AuthenticationType {
 
 /// A TACACS+ authentication service. Most of these values are only kept for backwards compatibility.
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum AuthenticationService {
     /// No authentication performed.
     None = 0x00,
@@ -207,6 +225,13 @@ pub enum AuthenticationService {
     FwProxy = 0x09,
 }
 
+#[doc(hidden)]
+impl From<TryFromPrimitiveError<AuthenticationService>> for DeserializeError {
+    fn from(value: TryFromPrimitiveError<AuthenticationService>) -> Self {
+        Self::InvalidAuthenticationService(value.number)
+    }
+}
+
 impl fmt::Display for AuthenticationService {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -229,6 +254,7 @@ impl fmt::Display for AuthenticationService {
 
 /// Some authentication information about a request, sent or received from a server.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct AuthenticationContext {
     /// The privilege level of the request.
     pub privilege_level: PrivilegeLevel,
@@ -250,6 +276,22 @@ impl AuthenticationContext {
         buffer[1] = self.authentication_type as u8;
         buffer[2] = self.service as u8;
     }
+
+    /// Deserializes authentication context information from the "header" of a packet body.
+    pub(super) fn deserialize(buffer: &[u8]) -> Result<Self, DeserializeError> {
+        if buffer.len() >= Self::WIRE_SIZE {
+            let privilege_level = PrivilegeLevel::new(buffer[0])
+                .ok_or(DeserializeError::InvalidPrivilegeLevel(buffer[0]))?;
+
+            Ok(Self {
+                privilege_level,
+                authentication_type: AuthenticationType::try_from(buffer[1])?,
+                service: AuthenticationService::try_from(buffer[2])?,
+            })
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
+    }
 }
 
 /// Some information about the user connected to a TACACS+ client.
@@ -320,6 +362,50 @@ impl<'info> UserInformation<'info> {
         }
     }
 
+    /// Computes the total length of the user information field values, based on the lengths
+    /// stored in the "header" of a client-sent packet body.
+    pub(super) fn extract_values_length(lengths: &[u8]) -> Result<usize, DeserializeError> {
+        if lengths.len() >= Self::HEADER_INFORMATION_SIZE {
+            Ok(lengths[..Self::HEADER_INFORMATION_SIZE]
+                .iter()
+                .map(|&length| length as usize)
+                .sum())
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
+    }
+
+    /// Deserializes user information from the encoded field lengths and the (contiguous) field values of a packet body.
+    ///
+    /// `values` may be longer than the user information fields, in which case the extra bytes are ignored.
+    pub(super) fn deserialize(
+        lengths: &[u8],
+        values: &'info [u8],
+    ) -> Result<Self, DeserializeError> {
+        let values_length = Self::extract_values_length(lengths)?;
+
+        if values.len() >= values_length {
+            let user_end = lengths[0] as usize;
+            let port_end = user_end + lengths[1] as usize;
+
+            // user can be any UTF-8 string, but port/remote address must be printable ASCII
+            let user =
+                core::str::from_utf8(&values[..user_end]).map_err(|_| DeserializeError::BadText)?;
+            let port = FieldText::try_from(&values[user_end..port_end])
+                .map_err(|_| DeserializeError::BadText)?;
+            let remote_address = FieldText::try_from(&values[port_end..values_length])
+                .map_err(|_| DeserializeError::BadText)?;
+
+            Ok(Self {
+                user,
+                port,
+                remote_address,
+            })
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
+    }
+
     /// Copies client information fields into their proper locations within a packet body.
     pub(super) fn serialize_field_values(
         &self,
@@ -22,11 +22,18 @@ pub mod authorization;
 
 mod packet;
 use getset::CopyGetters;
+pub use packet::any::{AnyPacket, PacketKind};
 pub use packet::header::HeaderInfo;
 pub use packet::{Packet, PacketFlags, PacketType};
 
+#[cfg(feature = "std")]
+pub use packet::any::{AnyPacketOwned, PacketKindOwned};
+
 mod arguments;
-pub use arguments::{Argument, Arguments, InvalidArgument};
+pub use arguments::{Argument, Arguments, ArgumentsIterator, InvalidArgument};
+
+mod decoder;
+pub use decoder::{FrameBuffer, FrameError, PacketDecoder};
 
 mod fields;
 pub use fields::*;
@@ -37,6 +44,9 @@ pub use text::{FieldText, InvalidText};
 #[cfg(feature = "std")]
 mod owned;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+
 /// An error that occurred when serializing a packet or any of its components into their binary format.
 #[non_exhaustive]
 #[derive(Debug, PartialEq, Eq)]
@@ -98,6 +108,24 @@ pub enum DeserializeError {
     /// Invalid arguments when deserializing
     InvalidArgument(InvalidArgument),
 
+    /// Invalid authentication method byte.
+    InvalidAuthenticationMethod(u8),
+
+    /// Invalid authentication action byte.
+    InvalidAction(u8),
+
+    /// Invalid privilege level (i.e., outside the range 0-15).
+    InvalidPrivilegeLevel(u8),
+
+    /// Invalid authentication type byte.
+    InvalidAuthenticationType(u8),
+
+    /// Invalid authentication service byte.
+    InvalidAuthenticationService(u8),
+
+    /// Authentication start packet had an invalid combination of fields.
+    InvalidStart(authentication::BadStart),
+
     /// Mismatch between expected/received packet types.
     PacketTypeMismatch {
         /// The expected packet type.
@@ -139,6 +167,12 @@ impl fmt::Display for DeserializeError {
                 num & 0b1111  // minor version is 4 lower bits
             ),
             Self::InvalidArgument(reason) => write!(f, "invalid argument: {reason}"),
+            Self::InvalidAuthenticationMethod(num) => write!(f, "invalid authentication method: {num:#x}"),
+            Self::InvalidAction(num) => write!(f, "invalid authentication action: {num:#x}"),
+            Self::InvalidPrivilegeLevel(num) => write!(f, "invalid privilege level: {num}"),
+            Self::InvalidAuthenticationType(num) => write!(f, "invalid authentication type: {num:#x}"),
+            Self::InvalidAuthenticationService(num) => write!(f, "invalid authentication service: {num:#x}"),
+            Self::InvalidStart(reason) => write!(f, "invalid authentication start packet: {reason}"),
             Self::BadText => write!(f, "text field was not printable ASCII"),
             Self::IncorrectUnencryptedFlag => write!(f, "unencrypted flag had an incorrect value"),
             Self::PacketTypeMismatch { expected, actual } => write!(f, "packet type mismatch: expected {expected:?} but got {actual:?}"),
@@ -155,10 +189,11 @@ mod error_impls {
     use std::fmt;
 
     use super::text::InvalidText;
-    use super::{DeserializeError, InvalidArgument, SerializeError};
+    use super::{DeserializeError, FrameError, InvalidArgument, SerializeError};
 
     impl Error for DeserializeError {}
     impl Error for SerializeError {}
+    impl Error for FrameError {}
     impl Error for InvalidArgument {}
     impl Error for super::authentication::BadStart {}
     impl Error for super::authentication::DataTooLong {}
@@ -194,6 +229,7 @@ mod sealed {
 #[repr(u8)]
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum MajorVersion {
     /// The only current major version specified in RFC8907.
     RFC8907 = 0xc,
@@ -215,6 +251,7 @@ impl fmt::Display for MajorVersion {
 #[repr(u8)]
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum MinorVersion {
     /// Default minor version, used for ASCII authentication.
     Default = 0x0,
@@ -237,6 +274,7 @@ impl fmt::Display for MinorVersion {
 
 /// The full protocol version.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, CopyGetters)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[getset(get_copy = "pub")]
 pub struct Version {
     /// The major TACACS+ version.
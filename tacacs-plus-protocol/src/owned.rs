@@ -28,7 +28,8 @@ where
 
 // boilerplate but necessary for above blanket Deserialize impl
 // NOTE: this also ignores the required_minor_version function which is irrelevant for every
-// packet type except authentication::Start, which doesn't have an owned variant as of now
+// packet type except authentication::Start; its owned variant is only obtained from an already-parsed
+// packet though, whose header already has the appropriate minor version
 impl<B: FromBorrowedBody> PacketBody for B {
     const TYPE: PacketType = <<B as FromBorrowedBody>::Borrowed<'_> as PacketBody>::TYPE;
     const REQUIRED_FIELDS_LENGTH: usize =
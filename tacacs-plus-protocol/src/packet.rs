@@ -10,6 +10,7 @@ use num_enum::{TryFromPrimitive, TryFromPrimitiveError};
 use super::{Deserialize, PacketBody, Serialize};
 use super::{DeserializeError, SerializeError};
 
+pub(super) mod any;
 pub(super) mod header;
 use header::HeaderInfo;
 
@@ -52,6 +53,7 @@ impl fmt::Display for PacketFlags {
 /// The type of a protocol packet.
 #[repr(u8)]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum PacketType {
     /// Authentication packet.
     Authentication = 0x1,
@@ -115,6 +117,30 @@ impl<B: PacketBody> Packet<B> {
     }
 }
 
+/// Reads the packet type from the header of a raw packet.
+fn raw_packet_type(buffer: &[u8]) -> Result<PacketType, DeserializeError> {
+    if buffer.len() > HeaderInfo::HEADER_SIZE_BYTES {
+        Ok(PacketType::try_from(buffer[1])?)
+    } else {
+        Err(DeserializeError::UnexpectedEnd)
+    }
+}
+
+/// Slices the body out of a raw packet, based on the body length reported in its header.
+fn body_section(buffer: &[u8]) -> Result<&[u8], DeserializeError> {
+    // body length is stored at the end of the 12-byte header
+    let body_length = NetworkEndian::read_u32(&buffer[8..12]) as usize;
+    let body_start = HeaderInfo::HEADER_SIZE_BYTES;
+
+    // NOTE: the rest of the buffer is checked here to avoid a panic if it's shorter than body_length when trying to slice that large
+    // ensure buffer actually contains whole body
+    if buffer[body_start..].len() >= body_length {
+        Ok(&buffer[body_start..body_start + body_length])
+    } else {
+        Err(DeserializeError::UnexpectedEnd)
+    }
+}
+
 /// MD5 hash output size, in bytes.
 const MD5_OUTPUT_SIZE: usize = 16;
 
@@ -136,25 +162,18 @@ pub(super) fn xor_body_with_pad(header: &HeaderInfo, secret_key: &[u8], body_buf
     prefix_hasher.update(u8::from(header.version()).to_be_bytes());
     prefix_hasher.update(header.sequence_number().to_be_bytes());
 
-    let mut chunks_iter = body_buffer.chunks_mut(MD5_OUTPUT_SIZE);
-
     // first chunk just uses hashed prefix
     prefix_hasher
         .clone()
         .finalize_into((&mut pseudo_pad).into());
 
-    // SAFETY: the body of a packet is guaranteed to be nonempty due to checks against REQUIRED_FIELD_SIZE,
-    // so this unwrap won't panic
-    let first_chunk = chunks_iter.next().unwrap();
-
-    // xor pseudo-pad with chunk
-    xor_slices(first_chunk, &pseudo_pad);
-
-    for chunk in chunks_iter {
-        // previous pad chunk is appended to prefix prehashed above
-        let mut hasher = prefix_hasher.clone();
-        hasher.update(pseudo_pad);
-        hasher.finalize_into((&mut pseudo_pad).into());
+    for (index, chunk) in body_buffer.chunks_mut(MD5_OUTPUT_SIZE).enumerate() {
+        // pad chunks after the first have the previous pad chunk appended to the prefix prehashed above
+        if index > 0 {
+            let mut hasher = prefix_hasher.clone();
+            hasher.update(pseudo_pad);
+            hasher.finalize_into((&mut pseudo_pad).into());
+        }
 
         // xor pseudo-pad with chunk
         xor_slices(chunk, &pseudo_pad);
@@ -244,7 +263,7 @@ impl<'raw, B: PacketBody + Deserialize<'raw>> Packet<B> {
         secret_key: K,
         buffer: &'raw mut [u8],
     ) -> Result<Self, DeserializeError> {
-        let header = HeaderInfo::try_from(&buffer[..HeaderInfo::HEADER_SIZE_BYTES])?;
+        let header = HeaderInfo::try_from(&*buffer)?;
 
         // ensure unencrypted flag is not set
         if !header.flags().contains(PacketFlags::UNENCRYPTED) {
@@ -267,7 +286,7 @@ impl<'raw, B: PacketBody + Deserialize<'raw>> Packet<B> {
     /// This function also ensures that the [`UNENCRYPTED`](PacketFlags::UNENCRYPTED)
     /// is set, and returns an error if it is not.
     pub fn deserialize_unobfuscated(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
-        let header = HeaderInfo::try_from(&buffer[..HeaderInfo::HEADER_SIZE_BYTES])?;
+        let header = HeaderInfo::try_from(buffer)?;
 
         // ensure unencrypted flag is set
         if header.flags().contains(PacketFlags::UNENCRYPTED) {
@@ -279,30 +298,14 @@ impl<'raw, B: PacketBody + Deserialize<'raw>> Packet<B> {
     }
 
     fn deserialize_body(buffer: &'raw [u8]) -> Result<B, DeserializeError> {
-        if buffer.len() > HeaderInfo::HEADER_SIZE_BYTES {
-            let actual_packet_type = PacketType::try_from(buffer[1])?;
-            if actual_packet_type == B::TYPE {
-                // body length is stored at the end of the 12-byte header
-                let body_length = NetworkEndian::read_u32(&buffer[8..12]) as usize;
-
-                // NOTE: the rest of the buffer is checked here to avoid a panic if it's shorter than body_length when trying to slice that large
-                // ensure buffer actually contains whole body
-                if buffer[Self::BODY_START..].len() >= body_length {
-                    let body = B::deserialize_from_buffer(
-                        &buffer[Self::BODY_START..Self::BODY_START + body_length],
-                    )?;
-                    Ok(body)
-                } else {
-                    Err(DeserializeError::UnexpectedEnd)
-                }
-            } else {
-                Err(DeserializeError::PacketTypeMismatch {
-                    expected: B::TYPE,
-                    actual: actual_packet_type,
-                })
-            }
+        let actual_packet_type = raw_packet_type(buffer)?;
+        if actual_packet_type == B::TYPE {
+            B::deserialize_from_buffer(body_section(buffer)?)
         } else {
-            Err(DeserializeError::UnexpectedEnd)
+            Err(DeserializeError::PacketTypeMismatch {
+                expected: B::TYPE,
+                actual: actual_packet_type,
+            })
         }
     }
 
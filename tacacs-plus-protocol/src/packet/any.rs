@@ -0,0 +1,252 @@
+//! Packets whose body type is only known once their header has been read.
+
+use getset::Getters;
+
+use super::{
+    body_section, raw_packet_type, xor_body_with_pad, HeaderInfo, PacketFlags, PacketType,
+};
+use crate::{accounting, authentication, authorization};
+use crate::{Deserialize, DeserializeError};
+
+/// The body of a packet, with its type determined from the packet header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PacketKind<'raw> {
+    /// An authentication start packet body.
+    AuthenticationStart(authentication::Start<'raw>),
+
+    /// An authentication continue packet body.
+    AuthenticationContinue(authentication::Continue<'raw>),
+
+    /// An authentication reply packet body.
+    AuthenticationReply(authentication::Reply<'raw>),
+
+    /// An authorization request packet body.
+    AuthorizationRequest(authorization::Request<'raw>),
+
+    /// An authorization reply packet body.
+    AuthorizationReply(authorization::Reply<'raw>),
+
+    /// An accounting request packet body.
+    AccountingRequest(accounting::Request<'raw>),
+
+    /// An accounting reply packet body.
+    AccountingReply(accounting::Reply<'raw>),
+}
+
+impl<'raw> PacketKind<'raw> {
+    /// Returns the type of packet this body belongs to.
+    pub fn packet_type(&self) -> PacketType {
+        match self {
+            Self::AuthenticationStart(_)
+            | Self::AuthenticationContinue(_)
+            | Self::AuthenticationReply(_) => PacketType::Authentication,
+            Self::AuthorizationRequest(_) | Self::AuthorizationReply(_) => {
+                PacketType::Authorization
+            }
+            Self::AccountingRequest(_) | Self::AccountingReply(_) => PacketType::Accounting,
+        }
+    }
+
+    /// Deserializes a packet body, choosing the body type based on the packet type and sequence number.
+    ///
+    /// Sequence numbers are odd for client packets and even for server packets, and an authentication
+    /// session always begins with a start packet, so a sequence number of 1 indicates a start packet
+    /// while any other odd number indicates a continue packet.
+    fn deserialize(
+        packet_type: PacketType,
+        sequence_number: u8,
+        body_buffer: &'raw [u8],
+    ) -> Result<Self, DeserializeError> {
+        let from_client = sequence_number % 2 == 1;
+
+        let kind = match (packet_type, from_client) {
+            (PacketType::Authentication, true) if sequence_number == 1 => {
+                Self::AuthenticationStart(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Authentication, true) => {
+                Self::AuthenticationContinue(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Authentication, false) => {
+                Self::AuthenticationReply(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Authorization, true) => {
+                Self::AuthorizationRequest(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Authorization, false) => {
+                Self::AuthorizationReply(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Accounting, true) => {
+                Self::AccountingRequest(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+            (PacketType::Accounting, false) => {
+                Self::AccountingReply(Deserialize::deserialize_from_buffer(body_buffer)?)
+            }
+        };
+
+        Ok(kind)
+    }
+}
+
+/// A full TACACS+ packet whose body type is determined at runtime from its header.
+///
+/// This is useful when the type of an incoming packet isn't known ahead of time, e.g. in a proxy.
+/// If it is known, [`Packet`](super::Packet) should be used instead.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Getters)]
+#[getset(get = "pub")]
+pub struct AnyPacket<'raw> {
+    /// Some of the header information associated with the packet.
+    header: HeaderInfo,
+
+    /// The body of the packet.
+    body: PacketKind<'raw>,
+}
+
+impl<'raw> AnyPacket<'raw> {
+    /// Attempts to deserialize an obfuscated packet of any type with the provided secret key.
+    ///
+    /// This function also ensures that the [`UNENCRYPTED`](PacketFlags::UNENCRYPTED)
+    /// is not set, and returns an error if it is.
+    pub fn deserialize<K: AsRef<[u8]>>(
+        secret_key: K,
+        buffer: &'raw mut [u8],
+    ) -> Result<Self, DeserializeError> {
+        let header = HeaderInfo::try_from(&*buffer)?;
+
+        // ensure unencrypted flag is not set
+        if !header.flags().contains(PacketFlags::UNENCRYPTED) {
+            xor_body_with_pad(
+                &header,
+                secret_key.as_ref(),
+                &mut buffer[HeaderInfo::HEADER_SIZE_BYTES..],
+            );
+
+            Self::deserialize_with_header(header, buffer)
+        } else {
+            Err(DeserializeError::IncorrectUnencryptedFlag)
+        }
+    }
+
+    /// Attempts to deserialize a cleartext packet of any type from a buffer.
+    ///
+    /// This function also ensures that the [`UNENCRYPTED`](PacketFlags::UNENCRYPTED)
+    /// is set, and returns an error if it is not.
+    pub fn deserialize_unobfuscated(buffer: &'raw [u8]) -> Result<Self, DeserializeError> {
+        let header = HeaderInfo::try_from(buffer)?;
+
+        // ensure unencrypted flag is set
+        if header.flags().contains(PacketFlags::UNENCRYPTED) {
+            Self::deserialize_with_header(header, buffer)
+        } else {
+            Err(DeserializeError::IncorrectUnencryptedFlag)
+        }
+    }
+
+    fn deserialize_with_header(
+        header: HeaderInfo,
+        buffer: &'raw [u8],
+    ) -> Result<Self, DeserializeError> {
+        let packet_type = raw_packet_type(buffer)?;
+        let body =
+            PacketKind::deserialize(packet_type, header.sequence_number(), body_section(buffer)?)?;
+
+        Ok(Self { header, body })
+    }
+
+    /// Converts this packet to one with a body that owns its fields.
+    #[cfg(feature = "std")]
+    pub fn to_owned(&self) -> AnyPacketOwned {
+        AnyPacketOwned {
+            header: self.header,
+            body: PacketKindOwned::from_borrowed(&self.body),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use owned::{AnyPacketOwned, PacketKindOwned};
+
+#[cfg(feature = "std")]
+mod owned {
+    use getset::Getters;
+
+    use super::{HeaderInfo, PacketKind, PacketType};
+    use crate::owned::FromBorrowedBody;
+    use crate::{accounting, authentication, authorization};
+
+    /// A packet body with owned fields, with its type determined from the packet header.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    pub enum PacketKindOwned {
+        /// An authentication start packet body.
+        AuthenticationStart(authentication::StartOwned),
+
+        /// An authentication continue packet body.
+        AuthenticationContinue(authentication::ContinueOwned),
+
+        /// An authentication reply packet body.
+        AuthenticationReply(authentication::ReplyOwned),
+
+        /// An authorization request packet body.
+        AuthorizationRequest(authorization::RequestOwned),
+
+        /// An authorization reply packet body.
+        AuthorizationReply(authorization::ReplyOwned),
+
+        /// An accounting request packet body.
+        AccountingRequest(accounting::RequestOwned),
+
+        /// An accounting reply packet body.
+        AccountingReply(accounting::ReplyOwned),
+    }
+
+    impl PacketKindOwned {
+        /// Returns the type of packet this body belongs to.
+        pub fn packet_type(&self) -> PacketType {
+            match self {
+                Self::AuthenticationStart(_)
+                | Self::AuthenticationContinue(_)
+                | Self::AuthenticationReply(_) => PacketType::Authentication,
+                Self::AuthorizationRequest(_) | Self::AuthorizationReply(_) => {
+                    PacketType::Authorization
+                }
+                Self::AccountingRequest(_) | Self::AccountingReply(_) => PacketType::Accounting,
+            }
+        }
+
+        pub(super) fn from_borrowed(borrowed: &PacketKind<'_>) -> Self {
+            match borrowed {
+                PacketKind::AuthenticationStart(start) => {
+                    Self::AuthenticationStart(FromBorrowedBody::from_borrowed(start))
+                }
+                PacketKind::AuthenticationContinue(cont) => {
+                    Self::AuthenticationContinue(FromBorrowedBody::from_borrowed(cont))
+                }
+                PacketKind::AuthenticationReply(reply) => {
+                    Self::AuthenticationReply(FromBorrowedBody::from_borrowed(reply))
+                }
+                PacketKind::AuthorizationRequest(request) => {
+                    Self::AuthorizationRequest(FromBorrowedBody::from_borrowed(request))
+                }
+                PacketKind::AuthorizationReply(reply) => {
+                    Self::AuthorizationReply(FromBorrowedBody::from_borrowed(reply))
+                }
+                PacketKind::AccountingRequest(request) => {
+                    Self::AccountingRequest(FromBorrowedBody::from_borrowed(request))
+                }
+                PacketKind::AccountingReply(reply) => {
+                    Self::AccountingReply(FromBorrowedBody::from_borrowed(reply))
+                }
+            }
+        }
+    }
+
+    /// A full TACACS+ packet with an owned body, whose type was determined at runtime from its header.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Getters)]
+    #[getset(get = "pub")]
+    pub struct AnyPacketOwned {
+        /// Some of the header information associated with the packet.
+        pub(super) header: HeaderInfo,
+
+        /// The body of the packet.
+        pub(super) body: PacketKindOwned,
+    }
+}
@@ -6,6 +6,7 @@ use crate::{DeserializeError, SerializeError, Version};
 
 /// Information included in a TACACS+ packet header.
 #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash, CopyGetters, MutGetters)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct HeaderInfo {
     #[getset(get_copy = "pub", get_mut = "pub(super)")]
     /// The protocol major and minor version.
@@ -68,15 +69,22 @@ impl HeaderInfo {
 impl TryFrom<&[u8]> for HeaderInfo {
     type Error = DeserializeError;
 
+    /// Attempts to parse header information from the beginning of a buffer.
+    ///
+    /// The buffer may be longer than a header, in which case the extra bytes are ignored.
     fn try_from(buffer: &[u8]) -> Result<Self, Self::Error> {
-        let header = Self {
-            version: buffer[0].try_into()?,
-            sequence_number: buffer[2],
-            flags: PacketFlags::from_bits(buffer[3])
-                .ok_or(DeserializeError::InvalidHeaderFlags(buffer[3]))?,
-            session_id: NetworkEndian::read_u32(&buffer[4..8]),
-        };
+        if buffer.len() >= Self::HEADER_SIZE_BYTES {
+            let header = Self {
+                version: buffer[0].try_into()?,
+                sequence_number: buffer[2],
+                flags: PacketFlags::from_bits(buffer[3])
+                    .ok_or(DeserializeError::InvalidHeaderFlags(buffer[3]))?,
+                session_id: NetworkEndian::read_u32(&buffer[4..8]),
+            };
 
-        Ok(header)
+            Ok(header)
+        } else {
+            Err(DeserializeError::UnexpectedEnd)
+        }
     }
 }
@@ -80,3 +80,235 @@ fn obfuscate_correct_pad_generated() {
         ]
     );
 }
+
+#[test]
+fn truncated_header_returns_error() {
+    let full_header = [
+        0xc << 4, // version
+        3,        // accounting packet
+        2,        // sequence number
+        0,        // no flags
+        // session id
+        0,
+        0,
+        0,
+        1,
+        // body length
+        0,
+        0,
+        0,
+        5,
+    ];
+
+    for length in 0..HeaderInfo::HEADER_SIZE_BYTES {
+        let mut truncated = full_header;
+        let truncated = &mut truncated[..length];
+
+        assert_eq!(
+            HeaderInfo::try_from(&*truncated),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Packet::<Reply>::deserialize_unobfuscated(truncated),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Packet::<Reply>::deserialize(b"key", truncated),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            crate::AnyPacket::deserialize(b"key", truncated),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+    }
+}
+
+#[test]
+fn obfuscated_packet_empty_body() {
+    let mut raw_packet = [
+        0xc << 4, // version
+        3,        // accounting packet
+        2,        // sequence number
+        0,        // no flags
+        // session id
+        0,
+        0,
+        0,
+        1,
+        // body length
+        0,
+        0,
+        0,
+        0,
+    ];
+
+    assert_eq!(
+        Packet::<Reply>::deserialize(b"key", &mut raw_packet),
+        Err(DeserializeError::UnexpectedEnd)
+    );
+}
+
+mod any_packet {
+    use super::*;
+
+    use crate::authentication::{self, Action, ContinueFlags};
+    use crate::{accounting, authorization};
+    use crate::{
+        AnyPacket, AuthenticationContext, AuthenticationService, AuthenticationType, FieldText,
+        PacketKind, PrivilegeLevel, UserInformation,
+    };
+
+    fn header(sequence_number: u8) -> HeaderInfo {
+        HeaderInfo::new(
+            Version::new(MajorVersion::RFC8907, MinorVersion::V1),
+            sequence_number,
+            PacketFlags::SINGLE_CONNECTION,
+            2468,
+        )
+    }
+
+    #[test]
+    fn authentication_start_by_sequence_number() {
+        let start = authentication::Start::new(
+            Action::Login,
+            AuthenticationContext {
+                privilege_level: PrivilegeLevel::new(1).unwrap(),
+                authentication_type: AuthenticationType::Pap,
+                service: AuthenticationService::Login,
+            },
+            UserInformation::new("user", FieldText::assert("tty0"), FieldText::assert("-"))
+                .unwrap(),
+            Some(b"password".as_slice().try_into().unwrap()),
+        )
+        .expect("start packet should be valid");
+        let packet = Packet::new(header(1), start);
+
+        let mut buffer = [0; 50];
+        let packet_length = packet
+            .clone()
+            .serialize(b"key", &mut buffer)
+            .expect("packet serialization should have succeeded");
+
+        let parsed = AnyPacket::deserialize(b"key", &mut buffer[..packet_length])
+            .expect("packet deserialization should have succeeded");
+
+        assert_eq!(parsed.header(), packet.header());
+        assert_eq!(parsed.body().packet_type(), PacketType::Authentication);
+        assert_eq!(
+            parsed.body(),
+            &PacketKind::AuthenticationStart(packet.body().clone())
+        );
+    }
+
+    #[test]
+    fn authentication_continue_by_sequence_number() {
+        let continue_body =
+            authentication::Continue::new(Some(b"hunter2"), None, ContinueFlags::empty())
+                .expect("continue packet should be valid");
+        let packet = Packet::new(header(3), continue_body);
+
+        let mut buffer = [0; 40];
+        let packet_length = packet
+            .clone()
+            .serialize_unobfuscated(&mut buffer)
+            .expect("packet serialization should have succeeded");
+
+        let parsed = AnyPacket::deserialize_unobfuscated(&buffer[..packet_length])
+            .expect("packet deserialization should have succeeded");
+        assert_eq!(
+            parsed.body(),
+            &PacketKind::AuthenticationContinue(packet.body().clone())
+        );
+    }
+
+    #[test]
+    fn reply_by_sequence_number_parity() {
+        let reply = authorization::Reply::new(
+            authorization::Status::Fail,
+            crate::Arguments::new(&[]).unwrap(),
+            FieldText::assert("denied"),
+            FieldText::assert(""),
+        )
+        .expect("reply fields should be valid");
+        let packet = Packet::new(header(2), reply);
+
+        let mut buffer = [0; 40];
+        let packet_length = packet
+            .clone()
+            .serialize(b"secret", &mut buffer)
+            .expect("packet serialization should have succeeded");
+
+        let parsed = AnyPacket::deserialize(b"secret", &mut buffer[..packet_length])
+            .expect("packet deserialization should have succeeded");
+        assert_eq!(
+            parsed.body(),
+            &PacketKind::AuthorizationReply(packet.body().clone())
+        );
+    }
+
+    #[test]
+    fn accounting_reply_to_owned() {
+        let reply = accounting::Reply::new(
+            accounting::Status::Success,
+            FieldText::assert("ok"),
+            FieldText::assert("logged"),
+        )
+        .expect("reply fields should be valid");
+        let packet = Packet::new(header(4), reply);
+
+        let mut buffer = [0; 40];
+        let packet_length = packet
+            .serialize_unobfuscated(&mut buffer)
+            .expect("packet serialization should have succeeded");
+
+        let parsed = AnyPacket::deserialize_unobfuscated(&buffer[..packet_length])
+            .expect("packet deserialization should have succeeded");
+
+        #[cfg(feature = "std")]
+        {
+            use crate::PacketKindOwned;
+            use std::string::ToString;
+
+            let owned = parsed.to_owned();
+            assert_eq!(owned.header(), parsed.header());
+            assert_eq!(
+                owned.body(),
+                &PacketKindOwned::AccountingReply(accounting::ReplyOwned {
+                    status: accounting::Status::Success,
+                    server_message: "ok".to_string(),
+                    data: "logged".to_string(),
+                })
+            );
+        }
+
+        #[cfg(not(feature = "std"))]
+        assert_eq!(parsed.body().packet_type(), PacketType::Accounting);
+    }
+
+    #[test]
+    fn any_packet_invalid_packet_type() {
+        let raw_packet = [
+            0xc << 4, // version
+            7,        // invalid packet type
+            1,        // sequence number
+            1,        // unencrypted flag
+            // session id
+            0,
+            0,
+            0,
+            0,
+            // body length
+            0,
+            0,
+            0,
+            1,
+            // body
+            0,
+        ];
+
+        assert_eq!(
+            AnyPacket::deserialize_unobfuscated(&raw_packet),
+            Err(DeserializeError::InvalidPacketType(7))
+        );
+    }
+}
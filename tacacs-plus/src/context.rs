@@ -1,6 +1,7 @@
-use tacacs_plus_protocol::{AuthenticationMethod, PrivilegeLevel, UserInformation};
+use tacacs_plus_protocol::{AuthenticationMethod, AuthenticationService};
+use tacacs_plus_protocol::{PrivilegeLevel, UserInformation};
 
-use super::ClientError;
+use super::{AuthenticationType, ClientError};
 
 pub(super) struct InvalidContext(());
 
@@ -18,6 +19,9 @@ pub struct SessionContext {
     pub(super) remote_address: String,
     pub(super) privilege_level: PrivilegeLevel,
     authentication_method: Option<AuthenticationMethod>,
+    authentication_type: Option<AuthenticationType>,
+    authentication_service: Option<AuthenticationService>,
+    pub(super) allow_restart: bool,
 }
 
 impl SessionContext {
@@ -43,6 +47,23 @@ impl SessionContext {
         self.authentication_method
             .unwrap_or(AuthenticationMethod::NotSet)
     }
+
+    /// Gets the authentication type for this context object, defaulting to [`NotSet`](tacacs_plus_protocol::AuthenticationType::NotSet).
+    ///
+    /// This should not be used within an authentication session.
+    pub(super) fn authentication_type(&self) -> tacacs_plus_protocol::AuthenticationType {
+        self.authentication_type
+            .map(AuthenticationType::as_protocol_type)
+            .unwrap_or(tacacs_plus_protocol::AuthenticationType::NotSet)
+    }
+
+    /// Gets the authentication service for this context object, defaulting to [`Login`](AuthenticationService::Login).
+    ///
+    /// This should not be used within an authentication session.
+    pub(super) fn authentication_service(&self) -> AuthenticationService {
+        self.authentication_service
+            .unwrap_or(AuthenticationService::Login)
+    }
 }
 
 /// Builder for [`SessionContext`] objects.
@@ -53,6 +74,9 @@ pub struct ContextBuilder {
     remote_address: String,
     privilege_level: PrivilegeLevel,
     authentication_method: Option<AuthenticationMethod>,
+    authentication_type: Option<AuthenticationType>,
+    authentication_service: Option<AuthenticationService>,
+    allow_restart: bool,
 }
 
 // TODO: don't consume builder at each step
@@ -65,6 +89,9 @@ impl ContextBuilder {
             remote_address: String::from("tacacs_plus_rs"),
             privilege_level: Default::default(),
             authentication_method: None,
+            authentication_type: None,
+            authentication_service: None,
+            allow_restart: false,
         }
     }
 
@@ -94,6 +121,36 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets the authentication type of the resulting context, i.e. how the user was authenticated.
+    ///
+    /// Note that this field is ignored in an authentication session.
+    pub fn auth_type(&mut self, authentication_type: AuthenticationType) -> &mut Self {
+        self.authentication_type = Some(authentication_type);
+        self
+    }
+
+    /// Sets the authentication service of the resulting context, i.e. the service through which the user was authenticated.
+    ///
+    /// This defaults to [`Login`](AuthenticationService::Login) if not set. Note that this field is also ignored in an
+    /// authentication session.
+    pub fn auth_service(&mut self, service: AuthenticationService) -> &mut Self {
+        self.authentication_service = Some(service);
+        self
+    }
+
+    /// Sets whether authentication sessions with the resulting context restart when requested by the server.
+    ///
+    /// If enabled, a session the server asks to restart (i.e., one with a response where
+    /// [`restart_types`](crate::AuthenticationResponse::restart_types) is set) is retried with the first type in
+    /// that list which hasn't been attempted yet. This only applies to [`Client::authenticate()`](crate::Client::authenticate)
+    /// and [`Client::enable()`](crate::Client::enable), since other sessions can't switch authentication types.
+    ///
+    /// This is disabled by default, in which case a restart is reported as a failure.
+    pub fn allow_restart(&mut self, allow: bool) -> &mut Self {
+        self.allow_restart = allow;
+        self
+    }
+
     /// Consumes this builder and turns it into a [`SessionContext`].
     pub fn build(&self) -> SessionContext {
         SessionContext {
@@ -102,6 +159,9 @@ impl ContextBuilder {
             remote_address: self.remote_address.clone(),
             privilege_level: self.privilege_level,
             authentication_method: self.authentication_method,
+            authentication_type: self.authentication_type,
+            authentication_service: self.authentication_service,
+            allow_restart: self.allow_restart,
         }
     }
 }
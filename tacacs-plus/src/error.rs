@@ -35,6 +35,15 @@ pub enum ClientError {
         user_message: String,
     },
 
+    /// An interactive authentication session was aborted by its prompter.
+    #[error("TACACS+ authentication session was aborted")]
+    AuthenticationAborted,
+
+    /// The authenticator response in an MS-CHAPv2 reply didn't match the expected value, so the server
+    /// couldn't be verified to know the user's password.
+    #[error("MS-CHAPv2 authenticator response from server was invalid")]
+    InvalidAuthenticatorResponse,
+
     // TODO: more descriptive error message
     /// Error when performing authorization.
     #[error("error when performing TACACS+ authorization")]
@@ -62,6 +71,12 @@ pub enum ClientError {
         admin_message: String,
     },
 
+    /// An operation didn't complete within its configured timeout.
+    ///
+    /// The connection used for the operation is dropped, so a new one is opened for the next session.
+    #[error("TACACS+ operation timed out")]
+    Timeout,
+
     /// Error when serializing a packet to the wire.
     #[error(transparent)]
     SerializeError(#[from] protocol::SerializeError),
@@ -70,6 +85,10 @@ pub enum ClientError {
     #[error("invalid packet received from server: {0}")]
     InvalidPacketReceived(#[from] protocol::DeserializeError),
 
+    /// A packet received from a server could not be framed, e.g. due to an overly long body.
+    #[error("invalid packet framing: {0}")]
+    InvalidFrameReceived(#[from] protocol::FrameError),
+
     /// Supplied data could not be encoded into a packet.
     #[error("packet could not be constructed from provided data")]
     InvalidPacketData,
@@ -113,6 +132,13 @@ pub enum ClientError {
     SystemTimeBeforeEpoch(#[from] std::time::SystemTimeError),
 }
 
+impl ClientError {
+    /// Checks whether this error was caused by the connection to a server rather than the server itself.
+    pub(super) fn is_transport_error(&self) -> bool {
+        matches!(self, ClientError::IOError(_) | ClientError::Timeout)
+    }
+}
+
 // authentication data being too long is a direct result of the password being too long
 // hidden since this is an implementation detail that isn't important to library consumers
 #[doc(hidden)]
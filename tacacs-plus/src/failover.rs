@@ -0,0 +1,272 @@
+//! Failover between several TACACS+ servers.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use futures::{AsyncRead, AsyncWrite, FutureExt};
+
+use super::{AccountingResponse, AuthenticationResponse, AuthorizationResponse, EnableResponse};
+use super::{AccountingTask, Argument, SessionContext};
+use super::{AuthenticationType, ChapResponse, OutboundAuthentication};
+use super::{Client, ClientError, SessionProgress};
+
+/// A client that fails over between an ordered list of TACACS+ servers.
+///
+/// Each server is represented by its own [`Client`], so each has its own connection factory and secret.
+/// Sessions are first attempted with the server that most recently replied to one (initially the first in the list),
+/// and are retried with the following servers in order if a transport error occurs.
+///
+/// Only [`ClientError::IOError`]s and [`ClientError::Timeout`]s cause a session to be retried with another server. A definitive response
+/// from a server, including an authentication failure or an error status, is returned as is.
+///
+/// Sessions that aren't idempotent (i.e., [`account_begin()`](FailoverClient::account_begin) and
+/// [`change_password()`](FailoverClient::change_password)) are only failed over by default if their request never
+/// reached the server (e.g., if a connection couldn't be opened), since a transport error after that point doesn't mean
+/// the server didn't act on the request; see
+/// [`with_non_idempotent_failover()`](FailoverClient::with_non_idempotent_failover) to fail them over regardless.
+///
+/// Interactive authentication sessions aren't supported, since a prompter would have to answer the same prompts again
+/// after a failover. The underlying clients can be accessed via [`servers()`](FailoverClient::servers) for those.
+pub struct FailoverClient<S> {
+    /// The servers sessions can be attempted with, in order of preference.
+    servers: Vec<Client<S>>,
+
+    /// The index of the server that most recently replied to a session.
+    last_working: AtomicUsize,
+
+    /// Whether sessions that aren't idempotent are failed over even after their request reached a server.
+    non_idempotent_failover: bool,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> FailoverClient<S> {
+    /// Creates a failover client from a list of per-server clients, in order of preference.
+    ///
+    /// Returns `None` if the list is empty.
+    pub fn new(servers: Vec<Client<S>>) -> Option<Self> {
+        if servers.is_empty() {
+            None
+        } else {
+            Some(Self {
+                servers,
+                last_working: AtomicUsize::new(0),
+                non_idempotent_failover: false,
+            })
+        }
+    }
+
+    /// Sets whether sessions that aren't idempotent are failed over to another server after a transport error,
+    /// even if their request was already sent to the server.
+    ///
+    /// This is disabled by default, since the server may have already acted on a request whose reply was lost;
+    /// e.g., a failed over accounting start could produce duplicate records, and a failed over password change
+    /// would be rejected by the next server if the first one already changed the password.
+    pub fn with_non_idempotent_failover(mut self, enabled: bool) -> Self {
+        self.non_idempotent_failover = enabled;
+        self
+    }
+
+    /// Returns the clients for each server, in order of preference.
+    pub fn servers(&self) -> &[Client<S>] {
+        &self.servers
+    }
+
+    /// Returns the client for the server that most recently replied to a session (or the first one, if none has).
+    pub fn last_working(&self) -> &Client<S> {
+        &self.servers[self.last_working.load(Ordering::Relaxed)]
+    }
+
+    /// Authenticates against a TACACS+ server with a username and password.
+    ///
+    /// See [`Client::authenticate()`] for more information.
+    pub async fn authenticate(
+        &self,
+        context: SessionContext,
+        password: &str,
+        authentication_type: AuthenticationType,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.with_failover(|client| {
+            client.authenticate(context.clone(), password, authentication_type)
+        })
+        .await
+    }
+
+    /// Requests the privilege level in the provided context from a TACACS+ server.
+    ///
+    /// See [`Client::enable()`] for more information.
+    pub async fn enable(
+        &self,
+        context: SessionContext,
+        password: &str,
+        authentication_type: AuthenticationType,
+    ) -> Result<EnableResponse, ClientError> {
+        self.with_failover(|client| client.enable(context.clone(), password, authentication_type))
+            .await
+    }
+
+    /// Relays a CHAP response obtained from a PPP peer to a TACACS+ server for verification.
+    ///
+    /// See [`Client::authenticate_chap_response()`] for more information.
+    pub async fn authenticate_chap_response(
+        &self,
+        context: SessionContext,
+        chap_response: ChapResponse<'_>,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.with_failover(|client| {
+            client.authenticate_chap_response(context.clone(), chap_response)
+        })
+        .await
+    }
+
+    /// Requests credentials from a TACACS+ server for authenticating this client to a remote peer.
+    ///
+    /// See [`Client::send_auth()`] for more information.
+    pub async fn send_auth(
+        &self,
+        context: SessionContext,
+        request: OutboundAuthentication<'_>,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.with_failover(|client| client.send_auth(context.clone(), request))
+            .await
+    }
+
+    /// Changes a user's password on a TACACS+ server.
+    ///
+    /// Once the session reached a server, this is only failed over if enabled via
+    /// [`with_non_idempotent_failover()`](FailoverClient::with_non_idempotent_failover).
+    /// See [`Client::change_password()`] for more information.
+    pub async fn change_password(
+        &self,
+        context: SessionContext,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.with_guarded_failover(|client| {
+            client.change_password_session(context.clone(), old_password, new_password)
+        })
+        .await
+    }
+
+    /// Performs TACACS+ authorization against a server with the provided arguments.
+    ///
+    /// See [`Client::authorize()`] for more information.
+    pub async fn authorize(
+        &self,
+        context: SessionContext,
+        arguments: Vec<Argument<'_>>,
+    ) -> Result<AuthorizationResponse, ClientError> {
+        self.with_failover(|client| client.authorize(context.clone(), arguments.clone()))
+            .await
+    }
+
+    /// Starts tracking a task via TACACS+ accounting.
+    ///
+    /// The returned task sends all further records to the server that accepted the start record. Once the start record
+    /// reached a server, it's only failed over if enabled via
+    /// [`with_non_idempotent_failover()`](FailoverClient::with_non_idempotent_failover).
+    /// See [`Client::account_begin()`] for more information.
+    pub async fn account_begin<'args, A: AsRef<[Argument<'args>]>>(
+        &self,
+        context: SessionContext,
+        arguments: A,
+    ) -> Result<(AccountingTask<&Client<S>>, AccountingResponse), ClientError> {
+        self.with_guarded_failover(|client| {
+            AccountingTask::start(client, context.clone(), arguments.as_ref())
+        })
+        .await
+    }
+
+    /// Runs an idempotent operation against each server in turn until one completes without a transport error,
+    /// starting from the one that last worked.
+    async fn with_failover<'client, T, F, Fut>(
+        &'client self,
+        mut operation: F,
+    ) -> Result<T, ClientError>
+    where
+        F: FnMut(&'client Client<S>) -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        self.run_failover(|client| operation(client).map(|result| (result, true)))
+            .await
+    }
+
+    /// Runs an operation that isn't idempotent against each server in turn, like [`with_failover()`](FailoverClient::with_failover).
+    ///
+    /// A session that reached a server isn't retried with the next one, unless failing those over was enabled.
+    async fn with_guarded_failover<'client, T, F, Fut>(
+        &'client self,
+        mut operation: F,
+    ) -> Result<T, ClientError>
+    where
+        F: FnMut(&'client Client<S>) -> Fut,
+        Fut: Future<Output = (Result<T, ClientError>, SessionProgress)>,
+    {
+        let non_idempotent_failover = self.non_idempotent_failover;
+
+        self.run_failover(|client| {
+            operation(client).map(move |(result, progress)| {
+                (
+                    result,
+                    non_idempotent_failover || progress == SessionProgress::Unsent,
+                )
+            })
+        })
+        .await
+    }
+
+    /// Runs an operation against each server in turn, starting from the one that last worked.
+    ///
+    /// The operation also reports whether a transport error it returns can be resolved by trying another server.
+    async fn run_failover<'client, T, F, Fut>(
+        &'client self,
+        mut operation: F,
+    ) -> Result<T, ClientError>
+    where
+        F: FnMut(&'client Client<S>) -> Fut,
+        Fut: Future<Output = (Result<T, ClientError>, bool)>,
+    {
+        let start = self.last_working.load(Ordering::Relaxed);
+        let mut last_error = None;
+
+        for offset in 0..self.servers.len() {
+            let index = (start + offset) % self.servers.len();
+
+            match operation(&self.servers[index]).await {
+                // errors from the connection rather than the server itself can be resolved by trying another server
+                (Err(error), true) if error.is_transport_error() => last_error = Some(error),
+                (result, _) => {
+                    // only a server that actually replied is known to be working
+                    if is_server_reply(&result) {
+                        self.last_working.store(index, Ordering::Relaxed);
+                    }
+
+                    return result;
+                }
+            }
+        }
+
+        // SAFETY: there's always at least one server, so the loop above ran at least once without returning
+        Err(last_error.unwrap())
+    }
+}
+
+/// Returns whether the result of a session reflects a reply from the server, as opposed to e.g. a malformed packet or a transport error.
+fn is_server_reply<T>(result: &Result<T, ClientError>) -> bool {
+    matches!(
+        result,
+        Ok(_)
+            | Err(ClientError::AuthenticationError { .. }
+                | ClientError::AuthorizationError { .. }
+                | ClientError::AccountingError { .. })
+    )
+}
+
+impl<S: fmt::Debug> fmt::Debug for FailoverClient<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FailoverClient")
+            .field("servers", &self.servers)
+            .field("last_working", &self.last_working)
+            .field("non_idempotent_failover", &self.non_idempotent_failover)
+            .finish()
+    }
+}
@@ -4,14 +4,16 @@ use std::fmt;
 use std::future::Future;
 use std::io;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex as StdMutex, PoisonError};
 use std::task::Poll;
+use std::time::{Duration, Instant};
 
-use byteorder::{ByteOrder, NetworkEndian};
 use futures::poll;
 use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tacacs_plus_protocol::{Deserialize, PacketBody, Serialize};
-use tacacs_plus_protocol::{HeaderInfo, Packet, PacketFlags};
+use tacacs_plus_protocol::{HeaderInfo, Packet, PacketDecoder, PacketFlags};
 
+use super::timeout::{self, TimeoutSettings};
 use super::ClientError;
 
 #[cfg(test)]
@@ -64,6 +66,11 @@ pub type ConnectionFuture<S> = Pin<Box<dyn Future<Output = io::Result<S>> + Send
 /// ```
 pub type ConnectionFactory<S> = Box<dyn Fn() -> ConnectionFuture<S> + Send>;
 
+/// A connection factory shared between all of the connections of a client.
+///
+/// The factory is wrapped in a mutex since it isn't required to be [`Sync`], but it's only locked while it's invoked.
+pub(super) type SharedFactory<S> = Arc<StdMutex<ConnectionFactory<S>>>;
+
 pub(super) struct ClientInner<S> {
     /// The underlying (TCP per RFC8907) connection for this client, if present.
     connection: Option<S>,
@@ -72,7 +79,7 @@ pub(super) struct ClientInner<S> {
     ///
     /// The factory is invoked whenever a new connection needs to be established, including when an ERROR status
     /// is reported by the server as well as for each new session if the server doesn't support single connection mode.
-    connection_factory: ConnectionFactory<S>,
+    connection_factory: SharedFactory<S>,
 
     /// Whether a session has been completed on the contained connection.
     first_session_completed: bool,
@@ -85,6 +92,9 @@ pub(super) struct ClientInner<S> {
     ///
     /// [RFC8907 section 4.3]: https://www.rfc-editor.org/rfc/rfc8907.html#section-4.3-5
     single_connection_established: bool,
+
+    /// When a packet was last sent or received on the contained connection, which is used to enforce the idle timeout.
+    last_active: Instant,
 }
 
 impl<S: fmt::Debug> fmt::Debug for ClientInner<S> {
@@ -101,20 +111,24 @@ impl<S: fmt::Debug> fmt::Debug for ClientInner<S> {
 }
 
 impl<S: AsyncRead + AsyncWrite + Unpin> ClientInner<S> {
-    pub(super) fn new(factory: ConnectionFactory<S>) -> Self {
+    pub(super) fn new(factory: SharedFactory<S>) -> Self {
         Self {
             connection: None,
             connection_factory: factory,
             first_session_completed: false,
             single_connection_established: false,
+            last_active: Instant::now(),
         }
     }
 
     /// NOTE: This function will open a new connection with the stored factory as needed.
-    async fn connection(&mut self) -> io::Result<&mut S> {
+    async fn connection(
+        &mut self,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<&mut S, ClientError> {
         // obtain new connection from factory
         if self.connection.is_none() {
-            let new_conn = (self.connection_factory)().await?;
+            let new_conn = connect(&self.connection_factory, timeouts).await?;
             self.connection = Some(new_conn);
         }
 
@@ -124,42 +138,62 @@ impl<S: AsyncRead + AsyncWrite + Unpin> ClientInner<S> {
         Ok(conn)
     }
 
-    /// Writes a packet to the underlying connection, reconnecting if necessary.
+    /// Writes a packet to the underlying connection, reconnecting if necessary when it starts a session.
+    ///
+    /// Later packets in a session are only sent on the connection the session started on, so an [`IOError`](ClientError::IOError)
+    /// is returned if it was closed.
+    ///
+    /// The header of the sent packet is returned, for consistency with multiplexed connections (which may change its session ID).
     pub(super) async fn send_packet<B: PacketBody + Serialize>(
         &mut self,
         packet: Packet<B>,
         secret_key: Option<&[u8]>,
-    ) -> Result<(), ClientError> {
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<HeaderInfo, ClientError> {
+        // a connection that's been idle for too long might have been dropped silently (e.g., by a firewall), so it isn't reused
+        // for a new session; a session in progress (e.g., one waiting on a prompter) has to stay on its connection though
+        let starts_session = packet.header().sequence_number() == 1;
+        if starts_session && self.is_idle(timeouts.and_then(|settings| settings.timeouts().idle)) {
+            self.close_connection().await;
+        }
+
         // check if other end closed our connection, and reopen it accordingly
-        let connection = self.connection().await?;
-        if !is_connection_open(connection).await? {
-            self.post_session_cleanup(true).await?;
+        if starts_session {
+            let connection = self.connection(timeouts).await?;
+            if !is_connection_open(connection).await? {
+                self.post_session_cleanup(true).await?;
+                self.connection(timeouts).await?;
+            }
         }
 
         // send the packet after ensuring the connection is valid (or dropping
         // it if it's invalid)
-        self._send_packet(packet, secret_key).await
+        let header = *packet.header();
+        self._send_packet(packet, secret_key).await?;
+
+        Ok(header)
     }
 
-    /// Writes a packet to the underlying connection.
+    /// Writes a packet to the underlying connection, which must already be open.
     async fn _send_packet<B: PacketBody + Serialize>(
         &mut self,
         packet: Packet<B>,
         secret_key: Option<&[u8]>,
     ) -> Result<(), ClientError> {
-        // allocate zero-filled buffer large enough to hold packet
-        let mut packet_buffer = vec![0; packet.wire_size()];
-
-        // obfuscate packet if we have a secret key
-        if let Some(key) = secret_key {
-            packet.serialize(key, &mut packet_buffer)?;
-        } else {
-            packet.serialize_unobfuscated(&mut packet_buffer)?;
-        }
+        let packet_buffer = serialize_packet(packet, secret_key)?;
 
-        let connection = self.connection().await?;
+        // a session can't continue on a different connection than it started on
+        let connection = self.connection.as_mut().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotConnected,
+                "connection was closed partway through a session",
+            )
+        })?;
         connection.write_all(&packet_buffer).await?;
-        connection.flush().await.map_err(Into::into)
+        connection.flush().await?;
+
+        self.last_active = Instant::now();
+        Ok(())
     }
 
     /// Receives a packet from the underlying connection.
@@ -167,39 +201,46 @@ impl<S: AsyncRead + AsyncWrite + Unpin> ClientInner<S> {
         &mut self,
         secret_key: Option<&[u8]>,
         expected_sequence_number: u8,
+        timeouts: Option<&TimeoutSettings>,
     ) -> Result<Packet<B>, ClientError>
     where
         B: PacketBody + for<'a> Deserialize<'a>,
     {
-        let mut buffer = vec![0; HeaderInfo::HEADER_SIZE_BYTES];
-        let buffer = &mut buffer;
-
-        let connection = self.connection().await?;
-        connection.read_exact(buffer).await?;
-
-        // read rest of body based on length reported in header
-        let body_length = NetworkEndian::read_u32(&buffer[8..12]);
-        buffer.resize(HeaderInfo::HEADER_SIZE_BYTES + body_length as usize, 0);
-        connection
-            .read_exact(&mut buffer[HeaderInfo::HEADER_SIZE_BYTES..])
-            .await?;
-
-        // unobfuscate packet as necessary
-        let deserialize_result: Packet<B> = if let Some(key) = secret_key {
-            Packet::deserialize(key, buffer)?
-        } else {
-            Packet::deserialize_unobfuscated(buffer)?
-        };
-
-        let actual_sequence_number = deserialize_result.header().sequence_number();
-        if actual_sequence_number == expected_sequence_number {
-            Ok(deserialize_result)
-        } else {
-            Err(ClientError::SequenceNumberMismatch {
-                expected: expected_sequence_number,
-                actual: actual_sequence_number,
-            })
+        let mut decoder = PacketDecoder::new(Vec::new());
+        let mut chunk = Vec::new();
+
+        let connection = self.connection(timeouts).await?;
+
+        // read exactly as much as the decoder needs, so no bytes from a subsequent packet are consumed
+        let read_result = timeout::limit(timeouts, |timeouts| timeouts.read, async {
+            while !decoder.is_complete() {
+                chunk.resize(decoder.bytes_needed(), 0);
+                connection.read_exact(&mut chunk).await?;
+                decoder.feed(&chunk)?;
+            }
+
+            Ok::<_, ClientError>(())
+        })
+        .await;
+
+        // the rest of a packet could still arrive after a timeout, and the rest of an invalid frame is never read,
+        // so the connection can't be used for anything else
+        if read_result.is_err() {
+            self.discard_connection();
+        }
+        read_result?;
+        self.last_active = Instant::now();
+
+        // SAFETY: the loop above only terminates once the decoder has a complete frame
+        let buffer = decoder.take_frame().unwrap();
+
+        // a packet that can't be parsed leaves the session (and so the connection) in an unknown state
+        let packet = deserialize_packet(buffer, secret_key, expected_sequence_number);
+        if packet.is_err() {
+            self.discard_connection();
         }
+
+        packet
     }
 
     /// NOTE: This function is separate from post_session_cleanup since it has to be done after the first reply/second packet
@@ -214,6 +255,41 @@ impl<S: AsyncRead + AsyncWrite + Unpin> ClientInner<S> {
         }
     }
 
+    /// Drops the current connection without closing it gracefully, e.g. if it's been left in an unknown state.
+    pub(super) fn discard_connection(&mut self) {
+        self.connection = None;
+
+        // reset connection status "flags", as a new one will be opened for the next session
+        self.single_connection_established = false;
+        self.first_session_completed = false;
+    }
+
+    /// Closes the current connection, if any, ignoring any errors since it's being dropped either way.
+    async fn close_connection(&mut self) {
+        if let Some(mut connection) = self.connection.take() {
+            let _ = connection.close().await;
+        }
+
+        self.discard_connection();
+    }
+
+    /// Whether the current connection has gone unused for longer than the provided idle timeout.
+    fn is_idle(&self, idle_timeout: Option<Duration>) -> bool {
+        self.connection.is_some()
+            && idle_timeout.is_some_and(|timeout| self.last_active.elapsed() >= timeout)
+    }
+
+    /// Closes the current connection if it's exceeded the provided idle timeout, or drops it if the server has closed it.
+    pub(super) async fn check_liveness(&mut self, idle_timeout: Option<Duration>) {
+        if self.is_idle(idle_timeout) {
+            self.close_connection().await;
+        } else if let Some(connection) = self.connection.as_mut() {
+            if !is_connection_open(connection).await.unwrap_or(false) {
+                self.discard_connection();
+            }
+        }
+    }
+
     pub(super) async fn post_session_cleanup(&mut self, status_is_error: bool) -> io::Result<()> {
         // close session if server doesn't agree to SINGLE_CONNECTION negotiation, or if an error occurred (since a mutex guarantees only one session is going at a time)
         if !self.single_connection_established || status_is_error {
@@ -235,6 +311,65 @@ impl<S: AsyncRead + AsyncWrite + Unpin> ClientInner<S> {
     }
 }
 
+/// Opens a new connection with the provided factory, subject to the connect timeout (if any).
+pub(super) async fn connect<S>(
+    factory: &SharedFactory<S>,
+    timeouts: Option<&TimeoutSettings>,
+) -> Result<S, ClientError> {
+    let connect = {
+        // a panic while invoking the factory doesn't leave it in an invalid state, so poisoning is ignored
+        let factory = factory.lock().unwrap_or_else(PoisonError::into_inner);
+        factory()
+    };
+
+    timeout::limit(timeouts, |timeouts| timeouts.connect, connect).await
+}
+
+/// Serializes a packet into a newly allocated buffer, obfuscating it if a secret key is provided.
+pub(super) fn serialize_packet<B: PacketBody + Serialize>(
+    packet: Packet<B>,
+    secret_key: Option<&[u8]>,
+) -> Result<Vec<u8>, ClientError> {
+    // allocate zero-filled buffer large enough to hold packet
+    let mut packet_buffer = vec![0; packet.wire_size()];
+
+    // obfuscate packet if we have a secret key
+    if let Some(key) = secret_key {
+        packet.serialize(key, &mut packet_buffer)?;
+    } else {
+        packet.serialize_unobfuscated(&mut packet_buffer)?;
+    }
+
+    Ok(packet_buffer)
+}
+
+/// Deserializes a received packet frame, unobfuscating it if a secret key is provided and ensuring it has the expected sequence number.
+pub(super) fn deserialize_packet<B>(
+    buffer: &mut [u8],
+    secret_key: Option<&[u8]>,
+    expected_sequence_number: u8,
+) -> Result<Packet<B>, ClientError>
+where
+    B: PacketBody + for<'a> Deserialize<'a>,
+{
+    // unobfuscate packet as necessary
+    let deserialize_result: Packet<B> = if let Some(key) = secret_key {
+        Packet::deserialize(key, buffer)?
+    } else {
+        Packet::deserialize_unobfuscated(buffer)?
+    };
+
+    let actual_sequence_number = deserialize_result.header().sequence_number();
+    if actual_sequence_number == expected_sequence_number {
+        Ok(deserialize_result)
+    } else {
+        Err(ClientError::SequenceNumberMismatch {
+            expected: expected_sequence_number,
+            actual: actual_sequence_number,
+        })
+    }
+}
+
 /// Checks if the provided connection is still open on both sides.
 ///
 /// This is accomplished by attempting to read a single byte from the connection
@@ -1,12 +1,15 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use futures::io::Cursor;
 use futures::AsyncWriteExt;
+use tacacs_plus_protocol::authentication::ReplyOwned;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::Notify;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
-use super::is_connection_open;
+use super::{is_connection_open, ClientInner, ConnectionFactory};
+use crate::ClientError;
 
 async fn bind_to_port(port: u16) -> TcpListener {
     TcpListener::bind(("localhost", port))
@@ -92,3 +95,30 @@ async fn connection_closed_check() {
         .expect("couldn't check if connection was open");
     assert!(!is_open);
 }
+
+#[tokio::test]
+async fn connection_discarded_after_invalid_frame() {
+    // an authentication reply header claiming a body longer than any valid one, followed by part of that body
+    let mut data = vec![0xc0, 0x01, 2, 0x01];
+    data.extend(1234u32.to_be_bytes());
+    data.extend(u32::MAX.to_be_bytes());
+    data.extend([0; 16]);
+
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        let data = data.clone();
+        Box::pin(async move { Ok(Cursor::new(data)) })
+    });
+    let mut inner = ClientInner::new(Arc::new(Mutex::new(factory)));
+
+    let error = inner
+        .receive_packet::<ReplyOwned>(None, 2, None)
+        .await
+        .expect_err("oversized frame should have been rejected");
+    assert!(
+        matches!(error, ClientError::InvalidFrameReceived(_)),
+        "unexpected error: {error:?}"
+    );
+
+    // the rest of the body is still on the connection, so it can't be reused
+    assert!(inner.connection.is_none());
+}
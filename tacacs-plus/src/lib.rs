@@ -5,15 +5,17 @@
 #![warn(missing_docs)]
 
 use std::fmt;
-use std::sync::Arc;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use futures::lock::Mutex;
 use futures::{AsyncRead, AsyncWrite};
 use rand::Rng;
 
 use tacacs_plus_protocol::Arguments;
+use tacacs_plus_protocol::AuthenticationContext;
 use tacacs_plus_protocol::{authentication, authorization};
-use tacacs_plus_protocol::{AuthenticationContext, AuthenticationService};
+use tacacs_plus_protocol::{Deserialize, PacketBody, Serialize};
 use tacacs_plus_protocol::{HeaderInfo, MajorVersion, MinorVersion, Version};
 use tacacs_plus_protocol::{Packet, PacketFlags};
 
@@ -22,30 +24,59 @@ pub use inner::{ConnectionFactory, ConnectionFuture};
 
 mod response;
 pub use response::{
-    AccountingResponse, AuthenticationResponse, AuthorizationResponse, ResponseStatus,
+    AccountingResponse, AuthenticationResponse, AuthorizationResponse, EnableResponse,
+    OutboundCredentials, ResponseStatus,
 };
 
 mod context;
 pub use context::{ContextBuilder, SessionContext};
 
+mod prompt;
+pub use prompt::{Prompt, PromptFuture, PromptKind, PromptResponse, Prompter};
+
 mod error;
 pub use error::ClientError;
 
 mod task;
 pub use task::AccountingTask;
 
+mod failover;
+pub use failover::FailoverClient;
+
+mod pool;
+
+mod multiplex;
+pub use multiplex::{BackgroundTask, Spawner};
+
+mod timeout;
+pub use timeout::{SleepFuture, Timeouts, Timer};
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod mschap;
+
+#[cfg(test)]
+mod tests;
+
 // reexported for ease of access
 pub use tacacs_plus_protocol as protocol;
-pub use tacacs_plus_protocol::{Argument, AuthenticationMethod, FieldText};
+pub use tacacs_plus_protocol::{Argument, AuthenticationMethod, AuthenticationService, FieldText};
 
 /// A TACACS+ client.
 #[derive(Clone)]
 pub struct Client<S> {
-    /// The underlying TCP connection of the client.
-    inner: Arc<Mutex<inner::ClientInner<S>>>,
+    /// The underlying TCP connections of the client.
+    pool: Arc<pool::ConnectionPool<S>>,
 
     /// The shared secret used for packet obfuscation, if provided.
     secret: Option<Vec<u8>>,
+
+    /// The timeouts for operations performed by this client, if any were set.
+    timeouts: Option<timeout::TimeoutSettings>,
+
+    /// The policy for retrying sessions after transport errors, if one was set.
+    retry: Option<retry::RetrySettings>,
 }
 
 /// The type of authentication used for a given session.
@@ -59,6 +90,64 @@ pub enum AuthenticationType {
     Pap,
     /// Authentication via the Challenge-Authentication Protocol (CHAP).
     Chap,
+    /// Authentication via version 1 of Microsoft's CHAP extensions (MS-CHAP).
+    MsChap,
+    /// Authentication via version 2 of Microsoft's CHAP extensions (MS-CHAPv2).
+    ///
+    /// If the server includes an authenticator response in a successful reply, it is verified
+    /// to ensure the server also knows the user's password.
+    MsChapV2,
+    /// Authentication via an ASCII login exchange, in which the server prompts for a username and password.
+    ///
+    /// [`Client::authenticate_interactive()`] can be used to answer arbitrary prompts instead.
+    Ascii,
+}
+
+impl AuthenticationType {
+    /// Converts this type to its corresponding protocol representation.
+    const fn as_protocol_type(self) -> protocol::AuthenticationType {
+        match self {
+            AuthenticationType::Pap => protocol::AuthenticationType::Pap,
+            AuthenticationType::Chap => protocol::AuthenticationType::Chap,
+            AuthenticationType::MsChap => protocol::AuthenticationType::MsChap,
+            AuthenticationType::MsChapV2 => protocol::AuthenticationType::MsChapV2,
+            AuthenticationType::Ascii => protocol::AuthenticationType::Ascii,
+        }
+    }
+}
+
+/// A CHAP response received from a PPP peer, to be verified by a TACACS+ server.
+///
+/// See [`Client::authenticate_chap_response()`] for more information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChapResponse<'data> {
+    /// The identifier of the CHAP exchange.
+    pub identifier: u8,
+
+    /// The challenge sent to the peer.
+    pub challenge: &'data [u8],
+
+    /// The MD5 response calculated by the peer.
+    pub response: &'data [u8; 16],
+}
+
+/// The type of credentials requested from a server for outbound authentication.
+///
+/// See [`Client::send_auth()`] for more information.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutboundAuthentication<'challenge> {
+    /// Requests a password to authenticate to a peer via PAP.
+    Pap,
+
+    /// Requests the response to a CHAP challenge received from a peer.
+    Chap {
+        /// The identifier of the CHAP exchange.
+        identifier: u8,
+
+        /// The challenge received from the peer.
+        challenge: &'challenge [u8],
+    },
 }
 
 impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
@@ -88,14 +177,115 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
         connection_factory: ConnectionFactory<S>,
         secret: Option<K>,
     ) -> Self {
-        let inner = inner::ClientInner::new(connection_factory);
+        let factory = Arc::new(Mutex::new(connection_factory));
 
         Self {
-            inner: Arc::new(Mutex::new(inner)),
+            pool: Arc::new(pool::ConnectionPool::new(factory, NonZeroUsize::MIN)),
             secret: secret.map(|s| s.as_ref().to_owned()),
+            timeouts: None,
+            retry: None,
         }
     }
 
+    /// Sets timeouts for the operations performed by this client, which are enforced using the provided [`Timer`].
+    ///
+    /// This allows the client to give up on an unresponsive server rather than waiting on it indefinitely,
+    /// which would otherwise block any other operations on this client as well. Operations that time out return
+    /// a [`ClientError::Timeout`].
+    pub fn with_timeouts<T: Timer + 'static>(mut self, timer: T, timeouts: Timeouts) -> Self {
+        self.timeouts = Some(timeout::TimeoutSettings::new(timer, timeouts));
+        self
+    }
+
+    /// Sets the maximum number of connections this client opens to the server, which defaults to 1.
+    ///
+    /// Each connection is used by one session at a time, so this is also the maximum number of sessions that can run in parallel;
+    /// additional sessions wait for a connection to become available. Connections are only opened as they're needed,
+    /// and each one negotiates single connection mode with the server separately.
+    ///
+    /// Any connections already opened by this client are not carried over, and clones of this client made
+    /// before calling this method continue to share their original connections. This also disables
+    /// [multiplexing](Self::with_multiplexing), if it was enabled.
+    pub fn with_max_connections(mut self, max_connections: NonZeroUsize) -> Self {
+        self.pool = Arc::new(pool::ConnectionPool::new(
+            self.pool.factory(),
+            max_connections,
+        ));
+        self
+    }
+
+    /// Runs concurrent sessions over a single connection once the server agrees to single connection mode, using the
+    /// provided [`Spawner`] to run a background task that reads replies from the connection.
+    ///
+    /// [RFC8907 section 4.3] allows a client to have multiple sessions in flight on a connection in single connection mode,
+    /// with replies matched to their session by session ID. The first session on each new connection negotiates
+    /// single connection mode, so other sessions wait for its first reply; if the server doesn't agree to it,
+    /// each session opens its own connection instead. Session IDs are kept unique among the sessions on a connection.
+    ///
+    /// As with [`with_max_connections()`](Self::with_max_connections), any connections already opened by this client
+    /// are not carried over.
+    ///
+    /// [RFC8907 section 4.3]: https://www.rfc-editor.org/rfc/rfc8907.html#section-4.3
+    pub fn with_multiplexing<E: Spawner + 'static>(mut self, spawner: E) -> Self
+    where
+        S: Send + 'static,
+    {
+        self.pool = Arc::new(pool::ConnectionPool::multiplexed(
+            self.pool.factory(),
+            Arc::new(spawner),
+        ));
+        self
+    }
+
+    /// Returns a background task that checks this client's idle connections at the provided interval, using the
+    /// provided [`Timer`] to wait between checks.
+    ///
+    /// Each check closes connections that have exceeded the [idle timeout](Timeouts::idle), if one is set, and drops
+    /// those that the server has closed, so the next session doesn't have to. Connections in use by a session are skipped.
+    ///
+    /// The task has to be run on an executor of your choice (e.g., with a [`Spawner`]), and finishes once this client
+    /// and all of its clones are dropped. It only uses the connections and idle timeout this client has when the task is
+    /// created, so it should be created after calling [`with_timeouts()`](Self::with_timeouts),
+    /// [`with_max_connections()`](Self::with_max_connections) or [`with_multiplexing()`](Self::with_multiplexing).
+    ///
+    /// A connection that was dropped silently (e.g., by a firewall) can't be detected this way, since no packets
+    /// are exchanged outside of sessions; an idle timeout or TCP keepalive (configured by the connection factory)
+    /// handles those instead.
+    pub fn liveness_check<T: Timer + 'static>(&self, timer: T, interval: Duration) -> BackgroundTask
+    where
+        S: Send + 'static,
+    {
+        let pool = Arc::downgrade(&self.pool);
+
+        // like the pool, the idle timeout is fixed once the task is created
+        let idle_timeout = self
+            .timeouts
+            .as_ref()
+            .and_then(|settings| settings.timeouts().idle);
+
+        Box::pin(async move {
+            loop {
+                timer.sleep(interval).await;
+
+                // the client and all of its clones have been dropped
+                let Some(pool) = pool.upgrade() else {
+                    break;
+                };
+
+                pool.check_liveness(idle_timeout).await;
+            }
+        })
+    }
+
+    /// Sets a policy for retrying sessions that fail due to transport errors, using the provided [`Timer`] to wait
+    /// between attempts.
+    ///
+    /// See [`RetryPolicy`] for which sessions are retried.
+    pub fn with_retry_policy<T: Timer + 'static>(mut self, timer: T, policy: RetryPolicy) -> Self {
+        self.retry = Some(retry::RetrySettings::new(timer, policy));
+        self
+    }
+
     fn make_header(&self, sequence_number: u8, minor_version: MinorVersion) -> HeaderInfo {
         // generate random id for this session
         // rand::ThreadRng implements CryptoRng, so it should be suitable for use as a CSPRNG
@@ -116,10 +306,11 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
         )
     }
 
-    fn pap_login_start_packet<'packet>(
+    fn pap_start_packet<'packet>(
         &self,
         context: &'packet SessionContext,
         password: &'packet str,
+        service: AuthenticationService,
     ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
         use protocol::authentication::BadStart;
 
@@ -132,13 +323,13 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
                 AuthenticationContext {
                     privilege_level: context.privilege_level,
                     authentication_type: protocol::AuthenticationType::Pap,
-                    service: AuthenticationService::Login,
+                    service,
                 },
                 context.as_user_information()?,
                 Some(password.as_bytes().try_into()?),
             )
             .map_err(|err| match err {
-                // SAFETY: the version, authentication type & saction fields are hard-coded to valid values so neither of these errors can occur
+                // SAFETY: the version, authentication type & action fields are hard-coded to valid values so neither of these errors can occur
                 BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
                 // we have to have a catch-all case since BadStart is marked #[non_exhaustive]
                 _ => ClientError::InvalidPacketData,
@@ -146,19 +337,19 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
         ))
     }
 
-    fn chap_login_start_packet<'packet>(
+    fn chap_start_packet<'packet>(
         &self,
         context: &'packet SessionContext,
         password: &'packet str,
+        service: AuthenticationService,
     ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
         use md5::{Digest, Md5};
-        use protocol::authentication::BadStart;
 
         // generate random PPP ID/challenge
         let ppp_id: u8 = rand::thread_rng().gen();
         let challenge = uuid::Uuid::new_v4();
 
-        // "The Response Value is the one-way hash calculated over a stream of octets consisting of the Identifier,
+        // "The Response Value is the one-way hash calculated over a stream of octets consisting of the Identifier,
         // followed by (concatenated with) the "secret", followed by (concatenated with) the Challenge Value."
         // RFC1334 section 3.2.1 ("Value" subheading): https://www.rfc-editor.org/rfc/rfc1334.html#section-3.2.1
         //
@@ -170,10 +361,71 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
         hasher.update(challenge);
         let response = hasher.finalize();
 
+        let chap_response = ChapResponse {
+            identifier: ppp_id,
+            challenge: challenge.as_bytes(),
+            response: &response.into(),
+        };
+
+        self.chap_response_start_packet(context, chap_response, service)
+    }
+
+    fn chap_response_start_packet<'packet>(
+        &self,
+        context: &'packet SessionContext,
+        chap_response: ChapResponse<'_>,
+        service: AuthenticationService,
+    ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
+        use protocol::authentication::BadStart;
+
         // "the data field is a concatenation of the PPP id, the challenge, and the response"
         // RFC8907 section 5.4.2.3: https://www.rfc-editor.org/rfc/rfc8907.html#section-5.4.2.3-2
+        let mut data = vec![chap_response.identifier];
+        data.extend(chap_response.challenge);
+        data.extend(chap_response.response);
+
+        Ok(Packet::new(
+            self.make_header(1, MinorVersion::V1),
+            authentication::Start::new(
+                authentication::Action::Login,
+                AuthenticationContext {
+                    privilege_level: context.privilege_level,
+                    authentication_type: protocol::AuthenticationType::Chap,
+                    service,
+                },
+                context.as_user_information()?,
+                // a long challenge is the only way the data can be too long, since the other fields are fixed-length
+                Some(
+                    data.try_into()
+                        .map_err(|_| ClientError::InvalidPacketData)?,
+                ),
+            )
+            .map_err(|err| match err {
+                // SAFETY: the version, authentication type & action fields are hard-coded to valid values so the start constructor will not fail
+                BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
+                _ => ClientError::InvalidPacketData,
+            })?,
+        ))
+    }
+
+    fn mschap_start_packet<'packet>(
+        &self,
+        context: &'packet SessionContext,
+        password: &'packet str,
+        service: AuthenticationService,
+    ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
+        use protocol::authentication::BadStart;
+
+        // generate random PPP ID/challenge
+        let ppp_id: u8 = rand::thread_rng().gen();
+        let challenge: [u8; mschap::V1_CHALLENGE_LENGTH] = rand::thread_rng().gen();
+
+        let response = mschap::v1_response(&challenge, password);
+
+        // "the data field is a concatenation of the PPP id, the MS-CHAP challenge, and the MS-CHAP response"
+        // RFC8907 section 5.4.2.4: https://www.rfc-editor.org/rfc/rfc8907.html#section-5.4.2.4-2
         let mut data = vec![ppp_id];
-        data.extend(challenge.as_bytes());
+        data.extend(challenge);
         data.extend(response);
 
         Ok(Packet::new(
@@ -182,8 +434,62 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
                 authentication::Action::Login,
                 AuthenticationContext {
                     privilege_level: context.privilege_level,
-                    authentication_type: protocol::AuthenticationType::Chap,
-                    service: AuthenticationService::Login,
+                    authentication_type: protocol::AuthenticationType::MsChap,
+                    service,
+                },
+                context.as_user_information()?,
+                Some(data.try_into()?),
+            )
+            .map_err(|err| match err {
+                // SAFETY: the version, authentication type & action fields are hard-coded to valid values so the start constructor will not fail
+                BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
+                _ => ClientError::InvalidPacketData,
+            })?,
+        ))
+    }
+
+    /// Builds an MS-CHAPv2 start packet, also returning the authenticator response expected from the server.
+    fn mschap_v2_start_packet<'packet>(
+        &self,
+        context: &'packet SessionContext,
+        password: &'packet str,
+        service: AuthenticationService,
+    ) -> Result<
+        (
+            Packet<authentication::Start<'packet>>,
+            [u8; mschap::AUTHENTICATOR_RESPONSE_LENGTH],
+        ),
+        ClientError,
+    > {
+        use protocol::authentication::BadStart;
+
+        // generate random PPP ID & challenges
+        // the client generates the authenticator challenge as well, since the server doesn't send one
+        let ppp_id: u8 = rand::thread_rng().gen();
+        let authenticator_challenge: [u8; mschap::V2_CHALLENGE_LENGTH] = rand::thread_rng().gen();
+        let peer_challenge: [u8; mschap::V2_CHALLENGE_LENGTH] = rand::thread_rng().gen();
+
+        let response = mschap::v2_response(
+            &authenticator_challenge,
+            &peer_challenge,
+            &context.user,
+            password,
+        );
+
+        // "the data field is a concatenation of the PPP id, the MS-CHAP challenge, and the MS-CHAP response"
+        // RFC8907 section 5.4.2.5: https://www.rfc-editor.org/rfc/rfc8907.html#section-5.4.2.5-2
+        let mut data = vec![ppp_id];
+        data.extend(authenticator_challenge);
+        data.extend(response.response);
+
+        let packet = Packet::new(
+            self.make_header(1, MinorVersion::V1),
+            authentication::Start::new(
+                authentication::Action::Login,
+                AuthenticationContext {
+                    privilege_level: context.privilege_level,
+                    authentication_type: protocol::AuthenticationType::MsChapV2,
+                    service,
                 },
                 context.as_user_information()?,
                 Some(data.try_into()?),
@@ -193,61 +499,669 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
                 BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
                 _ => ClientError::InvalidPacketData,
             })?,
+        );
+
+        Ok((packet, response.authenticator_response))
+    }
+
+    fn send_auth_start_packet<'packet>(
+        &self,
+        context: &'packet SessionContext,
+        request: OutboundAuthentication<'_>,
+    ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
+        use protocol::authentication::BadStart;
+
+        let (authentication_type, data) = match request {
+            // the server provides the password, so nothing is sent in the data field
+            OutboundAuthentication::Pap => (protocol::AuthenticationType::Pap, None),
+
+            // the data field contains the PPP id & challenge, and the server replies with the response
+            OutboundAuthentication::Chap {
+                identifier,
+                challenge,
+            } => {
+                let mut data = vec![identifier];
+                data.extend(challenge);
+
+                let data = data
+                    .try_into()
+                    .map_err(|_| ClientError::InvalidPacketData)?;
+                (protocol::AuthenticationType::Chap, Some(data))
+            }
+        };
+
+        Ok(Packet::new(
+            self.make_header(1, MinorVersion::V1),
+            authentication::Start::new(
+                authentication::Action::SendAuth,
+                AuthenticationContext {
+                    privilege_level: context.privilege_level,
+                    authentication_type,
+                    service: AuthenticationService::Ppp,
+                },
+                context.as_user_information()?,
+                data,
+            )
+            .map_err(|err| match err {
+                // SAFETY: the authentication type is either PAP or CHAP, both of which are compatible with the sendauth action
+                BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
+                _ => ClientError::InvalidPacketData,
+            })?,
+        ))
+    }
+
+    fn ascii_start_packet<'packet>(
+        &self,
+        context: &'packet SessionContext,
+        action: authentication::Action,
+        service: AuthenticationService,
+    ) -> Result<Packet<authentication::Start<'packet>>, ClientError> {
+        use protocol::authentication::BadStart;
+
+        Ok(Packet::new(
+            // ASCII authentication uses the default minor version
+            self.make_header(1, MinorVersion::Default),
+            authentication::Start::new(
+                action,
+                AuthenticationContext {
+                    privilege_level: context.privilege_level,
+                    authentication_type: protocol::AuthenticationType::Ascii,
+                    service,
+                },
+                context.as_user_information()?,
+                // the username is sent in the user information, and everything else is prompted for
+                None,
+            )
+            .map_err(|err| match err {
+                // SAFETY: the authentication type is hard-coded to ASCII, which is valid with the actions used by this client
+                BadStart::AuthTypeNotSet | BadStart::IncompatibleActionAndType => unreachable!(),
+                _ => ClientError::InvalidPacketData,
+            })?,
         ))
     }
 
     /// Authenticates against a TACACS+ server with a username and password using the specified protocol.
+    ///
+    /// For [`AuthenticationType::Ascii`], the username from the context and the provided password are sent
+    /// when the server prompts for them, and the session is aborted if the server asks for anything else.
     pub async fn authenticate(
         &self,
         context: SessionContext,
         password: &str,
         authentication_type: AuthenticationType,
     ) -> Result<AuthenticationResponse, ClientError> {
-        use protocol::authentication::ReplyOwned;
+        self.password_session(
+            context,
+            password,
+            authentication_type,
+            AuthenticationService::Login,
+        )
+        .await
+    }
+
+    /// Requests the privilege level in the provided context from a TACACS+ server (i.e., "enable" authentication),
+    /// using the specified protocol to provide the enable password.
+    ///
+    /// The privilege level is set via [`ContextBuilder::privilege_level()`]. Devices typically use PAP or
+    /// ASCII authentication for enable requests; see [`authenticate()`](Self::authenticate) for how the
+    /// password is sent for each authentication type.
+    pub async fn enable(
+        &self,
+        context: SessionContext,
+        password: &str,
+        authentication_type: AuthenticationType,
+    ) -> Result<EnableResponse, ClientError> {
+        let privilege_level = context.privilege_level;
+
+        self.password_session(
+            context,
+            password,
+            authentication_type,
+            AuthenticationService::Enable,
+        )
+        .await
+        .map(|response| EnableResponse::new(response, privilege_level))
+    }
+
+    /// Requests the privilege level in the provided context from a TACACS+ server via an ASCII exchange,
+    /// using the provided [`Prompter`] to answer each of the server's prompts.
+    ///
+    /// This otherwise behaves the same as [`authenticate_interactive()`](Self::authenticate_interactive).
+    pub async fn enable_interactive<P: Prompter + ?Sized>(
+        &self,
+        context: SessionContext,
+        prompter: &mut P,
+    ) -> Result<EnableResponse, ClientError> {
+        let privilege_level = context.privilege_level;
+
+        self.ascii_session(
+            context,
+            authentication::Action::Login,
+            AuthenticationService::Enable,
+            prompter,
+            false,
+        )
+        .await
+        .0
+        .map(|response| EnableResponse::new(response, privilege_level))
+    }
+
+    /// Performs an authentication session for the specified service, providing a password via the specified protocol.
+    async fn password_session(
+        &self,
+        context: SessionContext,
+        password: &str,
+        authentication_type: AuthenticationType,
+        service: AuthenticationService,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        let mut authentication_type = authentication_type;
+
+        // types that have already been tried are skipped to ensure a restart loop eventually ends
+        let mut attempted_types = vec![authentication_type];
+
+        loop {
+            let response = self
+                .password_session_attempt(context.clone(), password, authentication_type, service)
+                .await?;
+
+            let next_type = match &response.restart_types {
+                Some(types) if context.allow_restart => types
+                    .iter()
+                    .find(|restart_type| !attempted_types.contains(restart_type)),
+                _ => None,
+            };
+
+            match next_type {
+                Some(&next_type) => {
+                    authentication_type = next_type;
+                    attempted_types.push(next_type);
+                }
+                None => return Ok(response),
+            }
+        }
+    }
+
+    async fn password_session_attempt(
+        &self,
+        context: SessionContext,
+        password: &str,
+        authentication_type: AuthenticationType,
+        service: AuthenticationService,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        // the authenticator response expected from the server, for authentication types that support mutual authentication
+        let mut expected_authenticator = None;
 
         let start_packet = match authentication_type {
-            AuthenticationType::Pap => self.pap_login_start_packet(&context, password),
-            AuthenticationType::Chap => self.chap_login_start_packet(&context, password),
+            AuthenticationType::Pap => self.pap_start_packet(&context, password, service),
+            AuthenticationType::Chap => self.chap_start_packet(&context, password, service),
+            AuthenticationType::MsChap => self.mschap_start_packet(&context, password, service),
+            AuthenticationType::MsChapV2 => self
+                .mschap_v2_start_packet(&context, password, service)
+                .map(|(packet, authenticator)| {
+                    expected_authenticator = Some(authenticator);
+                    packet
+                }),
+            AuthenticationType::Ascii => {
+                let user = context.user.clone();
+                let mut prompter = prompt::PasswordPrompter::new(&user, password);
+
+                return self
+                    .ascii_session(
+                        context,
+                        authentication::Action::Login,
+                        service,
+                        &mut prompter,
+                        true,
+                    )
+                    .await
+                    .0;
+            }
         }?;
 
-        // block expression is used here to ensure that the connection mutex is only locked during communication
-        let reply = {
-            let secret_key = self.secret.as_deref();
+        let reply = self.single_reply_session(start_packet).await?;
 
-            let mut inner = self.inner.lock().await;
-            inner.send_packet(start_packet, secret_key).await?;
+        // the server proves it knows the password as well with an authenticator response, but it's only
+        // checked if one is actually included since servers don't necessarily send it
+        if let Some(expected) = expected_authenticator {
+            if reply.body().status == authentication::Status::Pass {
+                let received = mschap::parse_authenticator_response(&reply.body().data);
 
-            // response: whether authentication succeeded
-            let reply = inner.receive_packet::<ReplyOwned>(secret_key, 2).await?;
+                if received.is_some_and(|received| received != expected) {
+                    return Err(ClientError::InvalidAuthenticatorResponse);
+                }
+            }
+        }
+
+        authentication_response(reply.body())
+    }
 
+    /// Relays a CHAP response obtained from a PPP peer to a TACACS+ server for verification (i.e., CHAP pass-through).
+    ///
+    /// This is meant for PPP access servers, which only see the identifier, challenge and response
+    /// exchanged with a peer rather than its password. The session uses the
+    /// [`Ppp`](AuthenticationService::Ppp) authentication service, and the user in the context
+    /// should be the name the peer sent in its CHAP response.
+    ///
+    /// A [`ClientError::InvalidPacketData`] error is returned if the challenge is too long to fit in a packet
+    /// alongside the identifier and response.
+    pub async fn authenticate_chap_response(
+        &self,
+        context: SessionContext,
+        chap_response: ChapResponse<'_>,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        let start_packet =
+            self.chap_response_start_packet(&context, chap_response, AuthenticationService::Ppp)?;
+        let reply = self.single_reply_session(start_packet).await?;
+
+        authentication_response(reply.body())
+    }
+
+    /// Requests credentials from a TACACS+ server for authenticating this client to a remote peer, e.g. over PPP
+    /// (i.e., outbound authentication).
+    ///
+    /// The user in the context should be the name this client authenticates to the peer as. If the server
+    /// provides credentials, they are returned in the response's
+    /// [`outbound_credentials`](AuthenticationResponse::outbound_credentials) field.
+    ///
+    /// A [`ClientError::ProtocolError`] is returned if the server's reply indicates success but doesn't contain
+    /// valid credentials, i.e. a CHAP response of the wrong length.
+    pub async fn send_auth(
+        &self,
+        context: SessionContext,
+        request: OutboundAuthentication<'_>,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        let start_packet = self.send_auth_start_packet(&context, request)?;
+        let reply = self.single_reply_session(start_packet).await?;
+
+        let mut response = authentication_response(reply.body())?;
+
+        if response.status == ResponseStatus::Success {
+            // the server sends the credentials in the data field of its reply
+            let credentials = match request {
+                OutboundAuthentication::Pap => OutboundCredentials::Password(response.data.clone()),
+                OutboundAuthentication::Chap { .. } => match response.data.as_slice().try_into() {
+                    Ok(chap_response) => OutboundCredentials::ChapResponse(chap_response),
+                    Err(_) => {
+                        return Err(ClientError::ProtocolError {
+                            data: response.data,
+                            message: response.user_message,
+                        })
+                    }
+                },
+            };
+
+            response.outbound_credentials = Some(credentials);
+        }
+
+        Ok(response)
+    }
+
+    /// Sends an authentication start packet and receives the server's reply, for authentication types that
+    /// complete in a single exchange.
+    async fn single_reply_session(
+        &self,
+        start_packet: Packet<authentication::Start<'_>>,
+    ) -> Result<Packet<authentication::ReplyOwned>, ClientError> {
+        self.single_exchange(start_packet, false, |reply: &authentication::ReplyOwned| {
+            reply.status == authentication::Status::Error
+        })
+        .await
+        .0
+    }
+
+    /// Sends a packet and receives the server's reply, for sessions that consist of just those two packets.
+    ///
+    /// The provided function determines whether the reply has an error status, in which case the connection is reset.
+    /// The session is retried according to the client's retry policy if a transport error occurs before a reply is received,
+    /// although accounting sessions are only retried if the policy explicitly allows it.
+    ///
+    /// How far the session got across all attempts is returned as well.
+    async fn single_exchange<B, R>(
+        &self,
+        packet: Packet<B>,
+        accounting: bool,
+        is_error: impl Fn(&R) -> bool,
+    ) -> (Result<Packet<R>, ClientError>, SessionProgress)
+    where
+        B: PacketBody + Serialize + Clone,
+        R: PacketBody + for<'a> Deserialize<'a>,
+    {
+        let max_attempts = self
+            .retry
+            .as_ref()
+            .map_or(1, |retry| retry.max_attempts(accounting));
+
+        let mut attempt = 1;
+        let mut progress = SessionProgress::Unsent;
+
+        loop {
+            let (result, attempt_progress) = self
+                .single_exchange_attempt(packet.clone(), &is_error)
+                .await;
+
+            // an earlier attempt may have reached the server even if a later one didn't
+            progress = progress.max(attempt_progress);
+
+            match (result, &self.retry) {
+                // a reply from the server is definitive, so sessions are never retried after receiving one
+                (Err(error), Some(retry))
+                    if attempt_progress < SessionProgress::Replied
+                        && error.is_transport_error()
+                        && attempt < max_attempts =>
+                {
+                    retry.backoff(attempt).await;
+                    attempt += 1;
+                }
+                (result, _) => return (result, progress),
+            }
+        }
+    }
+
+    /// Performs a single attempt of a [`single_exchange()`](Client::single_exchange) session, additionally returning
+    /// how far it got.
+    async fn single_exchange_attempt<B, R>(
+        &self,
+        packet: Packet<B>,
+        is_error: impl Fn(&R) -> bool,
+    ) -> (Result<Packet<R>, ClientError>, SessionProgress)
+    where
+        B: PacketBody + Serialize + Clone,
+        R: PacketBody + for<'a> Deserialize<'a>,
+    {
+        let secret_key = self.secret.as_deref();
+        let timeouts = self.timeouts.as_ref();
+        let mut progress = SessionProgress::Unsent;
+
+        // the connection mutex is only locked for the duration of this function, i.e. during communication
+        let mut inner = self.pool.acquire().await;
+
+        let result = timeout::limit(timeouts, |timeouts| timeouts.session, async {
+            inner.send_packet(packet, secret_key, timeouts).await?;
+            progress = SessionProgress::Sent;
+
+            let reply = inner.receive_packet::<R>(secret_key, 2, timeouts).await?;
+            progress = SessionProgress::Replied;
+
+            // update inner state based on response
             inner.set_internal_single_connect_status(reply.header());
-            inner
-                .post_session_cleanup(reply.body().status == authentication::Status::Error)
-                .await?;
+            inner.post_session_cleanup(is_error(reply.body())).await?;
+
+            Ok::<_, ClientError>(reply)
+        })
+        .await;
 
-            reply
+        // a session that failed partway through leaves its connection in an unknown state, so it can't be reused
+        if result.is_err() {
+            inner.discard_connection().await;
+        }
+
+        (result, progress)
+    }
+
+    /// Authenticates against a TACACS+ server via an ASCII login exchange, using the provided [`Prompter`]
+    /// to answer each of the server's prompts.
+    ///
+    /// If the user in the provided context is nonempty, it is sent to the server up front, so servers
+    /// typically only prompt for a password. Each prompt includes the message sent by the server as well as
+    /// whether the input should be hidden as it's entered.
+    ///
+    /// One of the client's connections is held for the duration of the session, so other operations on this
+    /// client (or its clones) may wait until the prompter has finished if no other connections are available
+    /// (see [`with_max_connections()`](Self::with_max_connections)).
+    ///
+    /// If the prompter aborts the session, a [`ClientError::AuthenticationAborted`] error is returned.
+    pub async fn authenticate_interactive<P: Prompter + ?Sized>(
+        &self,
+        context: SessionContext,
+        prompter: &mut P,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.ascii_session(
+            context,
+            authentication::Action::Login,
+            AuthenticationService::Login,
+            prompter,
+            false,
+        )
+        .await
+        .0
+    }
+
+    /// Changes a user's password on a TACACS+ server.
+    ///
+    /// The server's prompts are answered in the order they're received: the username from the context is
+    /// sent if requested, the first password (or data) prompt is answered with the old password, and any
+    /// subsequent ones (e.g., to confirm the new password) are answered with the new password.
+    ///
+    /// A [`Success`](ResponseStatus::Success) status indicates the password was changed.
+    pub async fn change_password(
+        &self,
+        context: SessionContext,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.change_password_session(context, old_password, new_password)
+            .await
+            .0
+    }
+
+    /// Performs a [`change_password()`](Client::change_password) session, additionally returning how far it got.
+    async fn change_password_session(
+        &self,
+        context: SessionContext,
+        old_password: &str,
+        new_password: &str,
+    ) -> (Result<AuthenticationResponse, ClientError>, SessionProgress) {
+        let user = context.user.clone();
+        let mut prompter = prompt::ChangePasswordPrompter::new(&user, old_password, new_password);
+
+        self.ascii_session(
+            context,
+            authentication::Action::ChangePassword,
+            AuthenticationService::Login,
+            &mut prompter,
+            true,
+        )
+        .await
+    }
+
+    /// Changes a user's password on a TACACS+ server, using the provided [`Prompter`] to answer each of
+    /// the server's prompts.
+    ///
+    /// RFC8907 doesn't specify the exact prompts a server uses when changing a password, but servers typically
+    /// ask for the old password followed by the new one (possibly twice, for confirmation).
+    ///
+    /// As with [`authenticate_interactive()`](Self::authenticate_interactive), one of the client's connections is held
+    /// for the duration of the session, and a [`ClientError::AuthenticationAborted`] error is returned if the
+    /// prompter aborts the session.
+    pub async fn change_password_interactive<P: Prompter + ?Sized>(
+        &self,
+        context: SessionContext,
+        prompter: &mut P,
+    ) -> Result<AuthenticationResponse, ClientError> {
+        self.ascii_session(
+            context,
+            authentication::Action::ChangePassword,
+            AuthenticationService::Login,
+            prompter,
+            false,
+        )
+        .await
+        .0
+    }
+
+    /// Performs an ASCII authentication session with the specified action & service, answering server prompts with a [`Prompter`].
+    ///
+    /// If `canned_prompter` is set, the session is retried according to the client's retry policy if a transport error
+    /// occurs before a reply is received. Sessions with caller-supplied prompters are never retried, as those might not
+    /// expect to be used for more than one session.
+    ///
+    /// How far the session got across all attempts is returned as well.
+    async fn ascii_session<P: Prompter + ?Sized>(
+        &self,
+        context: SessionContext,
+        action: authentication::Action,
+        service: AuthenticationService,
+        prompter: &mut P,
+        canned_prompter: bool,
+    ) -> (Result<AuthenticationResponse, ClientError>, SessionProgress) {
+        let start_packet = match self.ascii_start_packet(&context, action, service) {
+            Ok(packet) => packet,
+            Err(error) => return (Err(error), SessionProgress::Unsent),
         };
 
-        let reply_status = ResponseStatus::try_from(reply.body().status);
-        let user_message = reply.body().server_message.clone();
-        let data = reply.body().data.clone();
+        let max_attempts = match &self.retry {
+            Some(retry) if canned_prompter => retry.max_attempts(false),
+            _ => 1,
+        };
 
-        match reply_status {
-            Ok(status) => Ok(AuthenticationResponse {
-                status,
-                user_message,
-                data,
-            }),
-            Err(response::BadAuthenticationStatus(status)) => {
-                Err(ClientError::AuthenticationError {
-                    status,
-                    data,
-                    user_message,
-                })
+        let mut attempt = 1;
+        let mut progress = SessionProgress::Unsent;
+
+        loop {
+            let (result, attempt_progress) = self
+                .ascii_session_attempt(start_packet.clone(), prompter)
+                .await;
+
+            progress = progress.max(attempt_progress);
+
+            match (result, &self.retry) {
+                // no prompts are answered before the first reply, so the prompter is still in its initial state
+                (Err(error), Some(retry))
+                    if attempt_progress < SessionProgress::Replied
+                        && error.is_transport_error()
+                        && attempt < max_attempts =>
+                {
+                    retry.backoff(attempt).await;
+                    attempt += 1;
+                }
+                (result, _) => {
+                    let response = result.and_then(|reply| authentication_response(reply.body()));
+                    return (response, progress);
+                }
             }
         }
     }
 
+    /// Performs a single attempt of an [`ascii_session()`](Client::ascii_session), additionally returning how far it got.
+    async fn ascii_session_attempt<P: Prompter + ?Sized>(
+        &self,
+        start_packet: Packet<authentication::Start<'_>>,
+        prompter: &mut P,
+    ) -> (
+        Result<Packet<authentication::ReplyOwned>, ClientError>,
+        SessionProgress,
+    ) {
+        use protocol::authentication::{ContinueFlags, ReplyFlags, ReplyOwned, Status};
+
+        let secret_key = self.secret.as_deref();
+        let timeouts = self.timeouts.as_ref();
+        let mut progress = SessionProgress::Unsent;
+
+        // the connection is locked for the entire session, since prompts are answered mid-session
+        let mut inner = self.pool.acquire().await;
+
+        let result = timeout::limit(timeouts, |timeouts| timeouts.session, async {
+            // the session ID might be changed to one that isn't in use on a multiplexed connection
+            let session_header = inner
+                .send_packet(start_packet, secret_key, timeouts)
+                .await?;
+            progress = SessionProgress::Sent;
+
+            let mut sequence_number = 2;
+
+            let reply = loop {
+                let reply = inner
+                    .receive_packet::<ReplyOwned>(secret_key, sequence_number, timeouts)
+                    .await?;
+                progress = SessionProgress::Replied;
+                inner.set_internal_single_connect_status(reply.header());
+
+                let kind = match reply.body().status {
+                    Status::GetUser => PromptKind::Username,
+                    Status::GetPassword => PromptKind::Password,
+                    Status::GetData => PromptKind::Data,
+
+                    // any other status ends the session
+                    _ => {
+                        inner
+                            .post_session_cleanup(reply.body().status == Status::Error)
+                            .await?;
+                        break reply;
+                    }
+                };
+
+                // sequence numbers can't wrap around, so there has to be room for a continue packet and another reply
+                if sequence_number >= u8::MAX - 1 {
+                    inner.post_session_cleanup(true).await?;
+                    return Err(ClientError::SequenceNumberOverflow);
+                }
+
+                let response = prompter
+                    .prompt(Prompt {
+                        kind,
+                        message: &reply.body().server_message,
+                        no_echo: reply.body().flags.contains(ReplyFlags::NO_ECHO),
+                    })
+                    .await;
+
+                let continue_header = HeaderInfo::new(
+                    session_header.version(),
+                    sequence_number + 1,
+                    session_header.flags(),
+                    session_header.session_id(),
+                );
+
+                match response {
+                    PromptResponse::Answer(answer) => {
+                        let Some(body) = authentication::Continue::new(
+                            Some(answer.as_bytes()),
+                            None,
+                            ContinueFlags::empty(),
+                        ) else {
+                            // the session can't continue without an answer, so the connection is reset
+                            inner.post_session_cleanup(true).await?;
+                            return Err(ClientError::InvalidPacketData);
+                        };
+
+                        inner
+                            .send_packet(Packet::new(continue_header, body), secret_key, timeouts)
+                            .await?;
+                    }
+                    PromptResponse::Abort(reason) => {
+                        // the abort reason is sent in the data field, truncated to the maximum length if necessary
+                        let reason = reason
+                            .as_ref()
+                            .map(|reason| &reason.as_bytes()[..reason.len().min(u16::MAX.into())]);
+
+                        // SAFETY: the abort reason was truncated to an encodable length above
+                        let body =
+                            authentication::Continue::new(None, reason, ContinueFlags::ABORT)
+                                .unwrap();
+                        inner
+                            .send_packet(Packet::new(continue_header, body), secret_key, timeouts)
+                            .await?;
+
+                        // the server doesn't reply to an abort, so the connection is reset to be safe
+                        inner.post_session_cleanup(true).await?;
+                        return Err(ClientError::AuthenticationAborted);
+                    }
+                }
+
+                sequence_number += 2;
+            };
+
+            Ok::<_, ClientError>(reply)
+        })
+        .await;
+
+        // a session that failed partway through leaves its connection in an unknown state, so it can't be reused
+        if result.is_err() {
+            inner.discard_connection().await;
+        }
+
+        (result, progress)
+    }
+
     /// Performs TACACS+ authorization against the server with the provided arguments.
     ///
     /// A merged `Vec` of all of the sent and received arguments is returned, with values replaced from
@@ -267,32 +1181,20 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
                 context.authentication_method(),
                 AuthenticationContext {
                     privilege_level: context.privilege_level,
-                    authentication_type: protocol::AuthenticationType::NotSet,
-                    // TODO: allow this to be specified as well? for guest it should probably be none
-                    service: AuthenticationService::Login,
+                    authentication_type: context.authentication_type(),
+                    service: context.authentication_service(),
                 },
                 context.as_user_information()?,
                 Arguments::new(&arguments).ok_or(ClientError::TooManyArguments)?,
             ),
         );
 
-        // the inner mutex is locked within a block to ensure it's only locked as long as necessary
-        let reply = {
-            let secret_key = self.secret.as_deref();
-
-            let mut inner = self.inner.lock().await;
-            inner.send_packet(request_packet, secret_key).await?;
-
-            let reply: Packet<ReplyOwned> = inner.receive_packet(secret_key, 2).await?;
-
-            // update inner state based on response
-            inner.set_internal_single_connect_status(reply.header());
-            inner
-                .post_session_cleanup(reply.body().status == authorization::Status::Error)
-                .await?;
-
-            reply
-        };
+        let reply = self
+            .single_exchange(request_packet, false, |reply: &ReplyOwned| {
+                reply.status == authorization::Status::Error
+            })
+            .await
+            .0?;
 
         let packet_status = reply.body().status;
         let user_message = reply.body().server_message.clone();
@@ -338,25 +1240,77 @@ impl<S: AsyncRead + AsyncWrite + Unpin> Client<S> {
         context: SessionContext,
         arguments: A,
     ) -> Result<(AccountingTask<&Self>, AccountingResponse), ClientError> {
-        AccountingTask::start(self, context, arguments).await
+        AccountingTask::start(self, context, arguments).await.0
     }
 }
 
 impl<S: fmt::Debug> fmt::Debug for Client<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // adapted from std mutex impl
-        let inner_debug = match self.inner.try_lock() {
-            Some(inner) => format!("{inner:?}"),
-            None => String::from("(locked)"),
-        };
-
         // we explicitly omit the secret here to avoid exposing it
         f.debug_struct("Client")
-            .field("inner", &inner_debug)
+            .field("connections", &self.pool)
+            .field("timeouts", &self.timeouts)
+            .field("retry", &self.retry)
             .finish_non_exhaustive()
     }
 }
 
+/// How far a session got before it finished, which determines whether it's safe to attempt again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SessionProgress {
+    /// The request was never written to the server, e.g. since a connection couldn't be opened.
+    Unsent,
+
+    /// The request was written to the server, but no reply was received.
+    Sent,
+
+    /// A reply was received from the server.
+    Replied,
+}
+
+/// Converts the final reply of an authentication session into a response, or an error if its status was unexpected.
+fn authentication_response(
+    reply: &authentication::ReplyOwned,
+) -> Result<AuthenticationResponse, ClientError> {
+    let user_message = reply.server_message.clone();
+    let data = reply.data.clone();
+
+    match ResponseStatus::try_from(reply.status) {
+        Ok(status) => Ok(AuthenticationResponse {
+            status,
+            user_message,
+            restart_types: (reply.status == authentication::Status::Restart)
+                .then(|| restart_types(&data)),
+            data,
+            outbound_credentials: None,
+        }),
+        Err(response::BadAuthenticationStatus(status)) => Err(ClientError::AuthenticationError {
+            status,
+            data,
+            user_message,
+        }),
+    }
+}
+
+/// Parses the authentication types listed in the data field of a restart reply, skipping any the client doesn't support.
+///
+/// Each octet of the data field is an authentication type the server will accept, per
+/// RFC8907 section 5.4.3: https://www.rfc-editor.org/rfc/rfc8907.html#section-5.4.3-6
+fn restart_types(data: &[u8]) -> Vec<AuthenticationType> {
+    data.iter()
+        .filter_map(
+            |&raw_type| match protocol::AuthenticationType::try_from(raw_type).ok()? {
+                protocol::AuthenticationType::Pap => Some(AuthenticationType::Pap),
+                protocol::AuthenticationType::Chap => Some(AuthenticationType::Chap),
+                protocol::AuthenticationType::MsChap => Some(AuthenticationType::MsChap),
+                protocol::AuthenticationType::MsChapV2 => Some(AuthenticationType::MsChapV2),
+                protocol::AuthenticationType::Ascii => Some(AuthenticationType::Ascii),
+                protocol::AuthenticationType::NotSet => None,
+            },
+        )
+        .collect()
+}
+
 /// Merges the sent & received arguments within a successful authorization session.
 ///
 /// Note that this assumes there are no duplicate arguments, as even RFC8907 is unclear
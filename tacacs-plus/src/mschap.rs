@@ -0,0 +1,240 @@
+//! Response calculation for Microsoft's PPP CHAP extensions (MS-CHAP).
+//!
+//! MS-CHAPv1 is specified in [RFC2433], and MS-CHAPv2 in [RFC2759].
+//!
+//! [RFC2433]: https://www.rfc-editor.org/rfc/rfc2433.html
+//! [RFC2759]: https://www.rfc-editor.org/rfc/rfc2759.html
+
+use des::cipher::{BlockEncrypt, KeyInit};
+use des::Des;
+use md4::{Digest, Md4};
+use sha1::Sha1;
+
+#[cfg(test)]
+mod tests;
+
+/// The length of an MS-CHAPv1 challenge, in bytes.
+pub(super) const V1_CHALLENGE_LENGTH: usize = 8;
+
+/// The length of an MS-CHAPv2 authenticator or peer challenge, in bytes.
+pub(super) const V2_CHALLENGE_LENGTH: usize = 16;
+
+/// The length of an MS-CHAPv2 authenticator response, in bytes.
+pub(super) const AUTHENTICATOR_RESPONSE_LENGTH: usize = 20;
+
+/// The length of an MS-CHAP response, in bytes.
+pub(super) const RESPONSE_LENGTH: usize = 49;
+
+/// The length of a password hash, in bytes.
+const HASH_LENGTH: usize = 16;
+
+/// The length of a challenge response calculated from a password hash, in bytes.
+const CHALLENGE_RESPONSE_LENGTH: usize = 24;
+
+/// The maximum number of password bytes used for the LAN Manager hash.
+const LM_PASSWORD_LENGTH: usize = 14;
+
+/// The constant that is encrypted with the password to produce the LAN Manager hash.
+const LM_MAGIC: &[u8; 8] = b"KGS!@#$%";
+
+/// The first constant used when generating an MS-CHAPv2 authenticator response.
+const AUTHENTICATOR_MAGIC_1: &[u8; 39] = b"Magic server to client signing constant";
+
+/// The second constant used when generating an MS-CHAPv2 authenticator response.
+const AUTHENTICATOR_MAGIC_2: &[u8; 41] = b"Pad to make it do more than one iteration";
+
+/// The prefix of an MS-CHAPv2 authenticator response when encoded as text.
+const AUTHENTICATOR_RESPONSE_PREFIX: &[u8; 2] = b"S=";
+
+/// Calculates an MS-CHAPv1 response to a challenge.
+///
+/// The response consists of the LAN Manager response, the NT response and a flags byte, which
+/// indicates to the server that the NT response should be preferred ([RFC2433 section 2]).
+///
+/// [RFC2433 section 2]: https://www.rfc-editor.org/rfc/rfc2433.html#section-2
+pub(super) fn v1_response(
+    challenge: &[u8; V1_CHALLENGE_LENGTH],
+    password: &str,
+) -> [u8; RESPONSE_LENGTH] {
+    let mut response = [0; RESPONSE_LENGTH];
+
+    response[..CHALLENGE_RESPONSE_LENGTH]
+        .copy_from_slice(&challenge_response(challenge, &lm_password_hash(password)));
+    response[CHALLENGE_RESPONSE_LENGTH..2 * CHALLENGE_RESPONSE_LENGTH]
+        .copy_from_slice(&challenge_response(challenge, &nt_password_hash(password)));
+
+    // "use Windows NT compatible challenge response" flag
+    response[RESPONSE_LENGTH - 1] = 1;
+
+    response
+}
+
+/// An MS-CHAPv2 response to a challenge, along with the authenticator response expected from the server.
+pub(super) struct V2Response {
+    /// The response to send to the server.
+    pub(super) response: [u8; RESPONSE_LENGTH],
+
+    /// The authenticator response the server should reply with if it knows the password as well.
+    pub(super) authenticator_response: [u8; AUTHENTICATOR_RESPONSE_LENGTH],
+}
+
+/// Calculates an MS-CHAPv2 response to an authenticator challenge, using the provided peer challenge.
+///
+/// The response consists of the peer challenge, 8 reserved bytes, the NT response and a (zero) flags byte
+/// ([RFC2759 section 4]).
+///
+/// [RFC2759 section 4]: https://www.rfc-editor.org/rfc/rfc2759.html#section-4
+pub(super) fn v2_response(
+    authenticator_challenge: &[u8; V2_CHALLENGE_LENGTH],
+    peer_challenge: &[u8; V2_CHALLENGE_LENGTH],
+    user: &str,
+    password: &str,
+) -> V2Response {
+    let challenge = challenge_hash(peer_challenge, authenticator_challenge, user);
+    let password_hash = nt_password_hash(password);
+    let nt_response = challenge_response(&challenge, &password_hash);
+
+    let mut response = [0; RESPONSE_LENGTH];
+    response[..V2_CHALLENGE_LENGTH].copy_from_slice(peer_challenge);
+
+    // the 8 bytes after the peer challenge are reserved, and the flags byte at the end must be zero
+    let nt_response_start = V2_CHALLENGE_LENGTH + 8;
+    response[nt_response_start..nt_response_start + CHALLENGE_RESPONSE_LENGTH]
+        .copy_from_slice(&nt_response);
+
+    V2Response {
+        response,
+        authenticator_response: authenticator_response(&password_hash, &nt_response, &challenge),
+    }
+}
+
+/// Extracts an MS-CHAPv2 authenticator response from the data field of a server reply, if present.
+///
+/// Authenticator responses are encoded as `S=` followed by 40 hexadecimal digits ([RFC2759 section 5]),
+/// potentially with other information around them (e.g., a message, or a PPP identifier before it).
+///
+/// [RFC2759 section 5]: https://www.rfc-editor.org/rfc/rfc2759.html#section-5
+pub(super) fn parse_authenticator_response(
+    data: &[u8],
+) -> Option<[u8; AUTHENTICATOR_RESPONSE_LENGTH]> {
+    let encoded_length = AUTHENTICATOR_RESPONSE_PREFIX.len() + 2 * AUTHENTICATOR_RESPONSE_LENGTH;
+
+    data.windows(encoded_length).find_map(|window| {
+        let hex_digits = window.strip_prefix(AUTHENTICATOR_RESPONSE_PREFIX)?;
+
+        let mut decoded = [0; AUTHENTICATOR_RESPONSE_LENGTH];
+        for (byte, digits) in decoded.iter_mut().zip(hex_digits.chunks(2)) {
+            let high = char::from(digits[0]).to_digit(16)?;
+            let low = char::from(digits[1]).to_digit(16)?;
+
+            // SAFETY: two hex digits always fit in a byte
+            *byte = u8::try_from(high << 4 | low).unwrap();
+        }
+
+        Some(decoded)
+    })
+}
+
+/// Calculates the 8-byte challenge used for an MS-CHAPv2 response (`ChallengeHash()` in RFC2759).
+fn challenge_hash(
+    peer_challenge: &[u8; V2_CHALLENGE_LENGTH],
+    authenticator_challenge: &[u8; V2_CHALLENGE_LENGTH],
+    user: &str,
+) -> [u8; 8] {
+    let mut hasher = Sha1::new();
+    hasher.update(peer_challenge);
+    hasher.update(authenticator_challenge);
+    hasher.update(user.as_bytes());
+
+    let mut challenge = [0; 8];
+    challenge.copy_from_slice(&hasher.finalize()[..8]);
+    challenge
+}
+
+/// Calculates the authenticator response a server sends to prove it knows the password
+/// (`GenerateAuthenticatorResponse()` in RFC2759).
+fn authenticator_response(
+    password_hash: &[u8; HASH_LENGTH],
+    nt_response: &[u8; CHALLENGE_RESPONSE_LENGTH],
+    challenge: &[u8; 8],
+) -> [u8; AUTHENTICATOR_RESPONSE_LENGTH] {
+    let password_hash_hash = Md4::digest(password_hash);
+
+    let mut hasher = Sha1::new();
+    hasher.update(password_hash_hash);
+    hasher.update(nt_response);
+    hasher.update(AUTHENTICATOR_MAGIC_1);
+    let digest = hasher.finalize();
+
+    let mut hasher = Sha1::new();
+    hasher.update(digest);
+    hasher.update(challenge);
+    hasher.update(AUTHENTICATOR_MAGIC_2);
+
+    hasher.finalize().into()
+}
+
+/// Hashes a password with MD4 after encoding it as UTF-16 (`NtPasswordHash()` in RFC2433).
+fn nt_password_hash(password: &str) -> [u8; HASH_LENGTH] {
+    let mut hasher = Md4::new();
+    for code_unit in password.encode_utf16() {
+        hasher.update(code_unit.to_le_bytes());
+    }
+
+    hasher.finalize().into()
+}
+
+/// Calculates the LAN Manager hash of a password (`LmPasswordHash()` in RFC2433).
+///
+/// Only the first 14 bytes of the password are used, after converting it to uppercase.
+fn lm_password_hash(password: &str) -> [u8; HASH_LENGTH] {
+    let mut uppercase_password = [0; LM_PASSWORD_LENGTH];
+    for (dest, byte) in uppercase_password.iter_mut().zip(password.bytes()) {
+        *dest = byte.to_ascii_uppercase();
+    }
+
+    let mut hash = [0; HASH_LENGTH];
+    for (key, output) in uppercase_password.chunks(7).zip(hash.chunks_mut(8)) {
+        // SAFETY: the password buffer is split into chunks of exactly 7 bytes
+        output.copy_from_slice(&des_encrypt(LM_MAGIC, key.try_into().unwrap()));
+    }
+
+    hash
+}
+
+/// Calculates the response to a challenge using a password hash (`ChallengeResponse()` in RFC2433).
+///
+/// The hash is zero-padded to 21 bytes, and each third of it is used as a DES key to encrypt the challenge.
+fn challenge_response(
+    challenge: &[u8; 8],
+    password_hash: &[u8; HASH_LENGTH],
+) -> [u8; CHALLENGE_RESPONSE_LENGTH] {
+    let mut padded_hash = [0; 21];
+    padded_hash[..HASH_LENGTH].copy_from_slice(password_hash);
+
+    let mut response = [0; CHALLENGE_RESPONSE_LENGTH];
+    for (key, output) in padded_hash.chunks(7).zip(response.chunks_mut(8)) {
+        // SAFETY: the padded hash is split into chunks of exactly 7 bytes
+        output.copy_from_slice(&des_encrypt(challenge, key.try_into().unwrap()));
+    }
+
+    response
+}
+
+/// Encrypts a single block with DES, using a 56-bit key without parity bits.
+fn des_encrypt(block: &[u8; 8], key: &[u8; 7]) -> [u8; 8] {
+    // spread the 56 key bits across 8 bytes, leaving the lowest (parity) bit of each byte unset
+    let mut expanded_key = [0; 8];
+    expanded_key[0] = key[0] & 0xfe;
+    for index in 1..7 {
+        expanded_key[index] = (key[index - 1] << (8 - index)) | (key[index] >> index);
+    }
+    expanded_key[7] = key[6] << 1;
+
+    // DES ignores parity bits, so they don't have to be set properly
+    let cipher = Des::new(&expanded_key.into());
+
+    let mut output = (*block).into();
+    cipher.encrypt_block(&mut output);
+    output.into()
+}
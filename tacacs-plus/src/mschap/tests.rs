@@ -0,0 +1,164 @@
+use super::{challenge_hash, challenge_response, lm_password_hash, nt_password_hash};
+use super::{parse_authenticator_response, v1_response, v2_response};
+
+// "SecREt01" vectors are from the NTLM documentation by Eric Glass: https://davenport.sourceforge.net/ntlm.html
+const NTLM_DOC_PASSWORD: &str = "SecREt01";
+const NTLM_DOC_CHALLENGE: [u8; 8] = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+const NTLM_DOC_LM_HASH: [u8; 16] = [
+    0xff, 0x37, 0x50, 0xbc, 0xc2, 0xb2, 0x24, 0x12, 0xc2, 0x26, 0x5b, 0x23, 0x73, 0x4e, 0x0d, 0xac,
+];
+
+const NTLM_DOC_NT_HASH: [u8; 16] = [
+    0xcd, 0x06, 0xca, 0x7c, 0x7e, 0x10, 0xc9, 0x9b, 0x1d, 0x33, 0xb7, 0x48, 0x5a, 0x2e, 0xd8, 0x08,
+];
+
+const NTLM_DOC_LM_RESPONSE: [u8; 24] = [
+    0xc3, 0x37, 0xcd, 0x5c, 0xbd, 0x44, 0xfc, 0x97, 0x82, 0xa6, 0x67, 0xaf, 0x6d, 0x42, 0x7c, 0x6d,
+    0xe6, 0x7c, 0x20, 0xc2, 0xd3, 0xe7, 0x7c, 0x56,
+];
+
+const NTLM_DOC_NT_RESPONSE: [u8; 24] = [
+    0x25, 0xa9, 0x8c, 0x1c, 0x31, 0xe8, 0x18, 0x47, 0x46, 0x6b, 0x29, 0xb2, 0xdf, 0x46, 0x80, 0xf3,
+    0x99, 0x58, 0xfb, 0x8c, 0x21, 0x3a, 0x9c, 0xc6,
+];
+
+#[test]
+fn nt_password_hash_rfc2759_vector() {
+    // RFC2759 section 9.2: https://www.rfc-editor.org/rfc/rfc2759.html#section-9.2
+    let hash = nt_password_hash("clientPass");
+    assert_eq!(
+        hash,
+        [
+            0x44, 0xeb, 0xba, 0x8d, 0x53, 0x12, 0xb8, 0xd6, 0x11, 0x47, 0x44, 0x11, 0xf5, 0x69,
+            0x89, 0xae
+        ]
+    );
+}
+
+#[test]
+fn challenge_response_rfc2759_vector() {
+    // RFC2759 section 9.2, where ChallengeResponse() is used to calculate the NT-Response from the challenge hash
+    let challenge = [0xd0, 0x2e, 0x43, 0x86, 0xbc, 0xe9, 0x12, 0x26];
+    let response = challenge_response(&challenge, &nt_password_hash("clientPass"));
+
+    assert_eq!(
+        response,
+        [
+            0x82, 0x30, 0x9e, 0xcd, 0x8d, 0x70, 0x8b, 0x5e, 0xa0, 0x8f, 0xaa, 0x39, 0x81, 0xcd,
+            0x83, 0x54, 0x42, 0x33, 0x11, 0x4a, 0x3d, 0x85, 0xd6, 0xdf
+        ]
+    );
+}
+
+#[test]
+fn password_hashes_ntlm_vectors() {
+    assert_eq!(lm_password_hash(NTLM_DOC_PASSWORD), NTLM_DOC_LM_HASH);
+    assert_eq!(nt_password_hash(NTLM_DOC_PASSWORD), NTLM_DOC_NT_HASH);
+}
+
+#[test]
+fn v1_response_ntlm_vectors() {
+    let response = v1_response(&NTLM_DOC_CHALLENGE, NTLM_DOC_PASSWORD);
+
+    assert_eq!(response[..24], NTLM_DOC_LM_RESPONSE);
+    assert_eq!(response[24..48], NTLM_DOC_NT_RESPONSE);
+
+    // NT response should be marked as preferred
+    assert_eq!(response[48], 1);
+}
+
+#[test]
+fn lm_password_hash_truncates_long_password() {
+    // only the first 14 characters are used, and they're case-insensitive
+    assert_eq!(
+        lm_password_hash("secret01secret01 and then some"),
+        lm_password_hash("SECRET01SECRET")
+    );
+}
+
+// RFC2759 section 9.2: https://www.rfc-editor.org/rfc/rfc2759.html#section-9.2
+const RFC2759_USER: &str = "User";
+const RFC2759_PASSWORD: &str = "clientPass";
+
+const RFC2759_AUTHENTICATOR_CHALLENGE: [u8; 16] = [
+    0x5b, 0x5d, 0x7c, 0x7d, 0x7b, 0x3f, 0x2f, 0x3e, 0x3c, 0x2c, 0x60, 0x21, 0x32, 0x26, 0x26, 0x28,
+];
+
+const RFC2759_PEER_CHALLENGE: [u8; 16] = [
+    0x21, 0x40, 0x23, 0x24, 0x25, 0x5e, 0x26, 0x2a, 0x28, 0x29, 0x5f, 0x2b, 0x3a, 0x33, 0x7c, 0x7e,
+];
+
+const RFC2759_NT_RESPONSE: [u8; 24] = [
+    0x82, 0x30, 0x9e, 0xcd, 0x8d, 0x70, 0x8b, 0x5e, 0xa0, 0x8f, 0xaa, 0x39, 0x81, 0xcd, 0x83, 0x54,
+    0x42, 0x33, 0x11, 0x4a, 0x3d, 0x85, 0xd6, 0xdf,
+];
+
+const RFC2759_AUTHENTICATOR_RESPONSE: [u8; 20] = [
+    0x40, 0x7a, 0x55, 0x89, 0x11, 0x5f, 0xd0, 0xd6, 0x20, 0x9f, 0x51, 0x0f, 0xe9, 0xc0, 0x45, 0x66,
+    0x93, 0x2c, 0xda, 0x56,
+];
+
+#[test]
+fn challenge_hash_rfc2759_vector() {
+    let challenge = challenge_hash(
+        &RFC2759_PEER_CHALLENGE,
+        &RFC2759_AUTHENTICATOR_CHALLENGE,
+        RFC2759_USER,
+    );
+    assert_eq!(challenge, [0xd0, 0x2e, 0x43, 0x86, 0xbc, 0xe9, 0x12, 0x26]);
+}
+
+#[test]
+fn v2_response_rfc2759_vector() {
+    let v2 = v2_response(
+        &RFC2759_AUTHENTICATOR_CHALLENGE,
+        &RFC2759_PEER_CHALLENGE,
+        RFC2759_USER,
+        RFC2759_PASSWORD,
+    );
+
+    assert_eq!(v2.response[..16], RFC2759_PEER_CHALLENGE);
+    assert_eq!(v2.response[16..24], [0; 8], "reserved bytes should be zero");
+    assert_eq!(v2.response[24..48], RFC2759_NT_RESPONSE);
+    assert_eq!(v2.response[48], 0, "flags should be zero");
+
+    assert_eq!(v2.authenticator_response, RFC2759_AUTHENTICATOR_RESPONSE);
+}
+
+#[test]
+fn parse_authenticator_response_text() {
+    let data = b"S=407A5589115FD0D6209F510FE9C04566932CDA56";
+    assert_eq!(
+        parse_authenticator_response(data),
+        Some(RFC2759_AUTHENTICATOR_RESPONSE)
+    );
+}
+
+#[test]
+fn parse_authenticator_response_with_surrounding_data() {
+    // PPP identifier before the response, and a message after it (lowercase hex digits are accepted too)
+    let data = b"\x01S=407a5589115fd0d6209f510fe9c04566932cda56 M=welcome";
+    assert_eq!(
+        parse_authenticator_response(data),
+        Some(RFC2759_AUTHENTICATOR_RESPONSE)
+    );
+}
+
+#[test]
+fn parse_authenticator_response_absent_or_invalid() {
+    assert_eq!(parse_authenticator_response(b""), None);
+    assert_eq!(parse_authenticator_response(b"some other data"), None);
+
+    // too few digits
+    assert_eq!(
+        parse_authenticator_response(b"S=407A5589115FD0D6209F510FE9C04566932CDA5"),
+        None
+    );
+
+    // non-hex digit
+    assert_eq!(
+        parse_authenticator_response(b"S=407A5589115FD0D6209F510FE9C04566932CDA5G"),
+        None
+    );
+}
@@ -0,0 +1,553 @@
+//! Multiplexing concurrent sessions over a single connection, as allowed by single connection mode.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex as StdMutex, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+use futures::channel::{mpsc, oneshot};
+use futures::future;
+use futures::io::{ReadHalf, WriteHalf};
+use futures::lock::{Mutex, MutexGuard};
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, StreamExt};
+use rand::Rng;
+use tacacs_plus_protocol::{Deserialize, PacketBody, Serialize};
+use tacacs_plus_protocol::{HeaderInfo, Packet, PacketDecoder, PacketFlags};
+
+use super::inner::{self, SharedFactory};
+use super::timeout::{self, TimeoutSettings};
+use super::ClientError;
+
+/// A (pinned, boxed) future that runs in the background for as long as a connection is open, as passed to a [`Spawner`].
+pub type BackgroundTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// An executor for background tasks, which allows a [`Client`](super::Client) to read replies to multiplexed sessions
+/// without depending on a specific async runtime.
+///
+/// # Examples
+///
+/// ```
+/// use tacacs_plus::{BackgroundTask, Spawner};
+///
+/// /// A spawner backed by the Tokio runtime.
+/// struct TokioSpawner;
+///
+/// impl Spawner for TokioSpawner {
+///     fn spawn(&self, task: BackgroundTask) {
+///         tokio::spawn(task);
+///     }
+/// }
+/// ```
+pub trait Spawner: Send + Sync {
+    /// Runs the provided task to completion in the background.
+    fn spawn(&self, task: BackgroundTask);
+}
+
+/// Creates the task that reads replies from a newly opened connection.
+///
+/// This is a function pointer so the bounds required to spawn the task only have to be satisfied when a
+/// [`Multiplexer`] is created, rather than by every [`Client`](super::Client) method.
+type ReaderTask<S> =
+    fn(ReadHalf<S>, Weak<SharedConnection<S>>, oneshot::Receiver<()>) -> BackgroundTask;
+
+/// Shares connections between concurrent sessions once the server agrees to single connection mode.
+pub(super) struct Multiplexer<S> {
+    /// The factory used to open new connections.
+    factory: SharedFactory<S>,
+
+    /// The spawner used to run the reader task of each connection.
+    spawner: Arc<dyn Spawner>,
+
+    /// Creates the reader task for a new connection.
+    reader_task: ReaderTask<S>,
+
+    /// The connection new sessions are started on, if single connection mode has been established for one.
+    ///
+    /// The lock is held by the first session on a new connection until its first reply arrives, since only
+    /// then is it known whether other sessions can share the connection.
+    current: Mutex<Option<Arc<SharedConnection<S>>>>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Multiplexer<S> {
+    pub(super) fn new(factory: SharedFactory<S>, spawner: Arc<dyn Spawner>) -> Self
+    where
+        S: Send + 'static,
+    {
+        Self {
+            factory,
+            spawner,
+            reader_task: |reader, connection, shutdown| {
+                Box::pin(read_replies(reader, connection, shutdown))
+            },
+            current: Mutex::new(None),
+        }
+    }
+
+    /// Starts a new session, which is attached to a connection when it sends its first packet.
+    pub(super) fn session(&self) -> MultiplexedSession<'_, S> {
+        MultiplexedSession {
+            multiplexer: self,
+            connection: None,
+            negotiation: None,
+            shared: false,
+            registration: None,
+        }
+    }
+
+    /// Opens a new connection and spawns the task that reads its replies.
+    async fn connect(
+        &self,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<Arc<SharedConnection<S>>, ClientError> {
+        let (reader, writer) = inner::connect(&self.factory, timeouts).await?.split();
+        let (shutdown_sender, shutdown) = oneshot::channel();
+
+        let connection = Arc::new(SharedConnection {
+            writer: Mutex::new(writer),
+            sessions: StdMutex::new(Sessions {
+                replies: HashMap::new(),
+                shutdown: Some(shutdown_sender),
+                last_active: Instant::now(),
+            }),
+        });
+
+        self.spawner.spawn((self.reader_task)(
+            reader,
+            Arc::downgrade(&connection),
+            shutdown,
+        ));
+
+        Ok(connection)
+    }
+
+    /// Closes the current connection if it's exceeded the provided idle timeout, or forgets it if it's been closed.
+    pub(super) fn check_liveness(&self, idle_timeout: Option<Duration>) {
+        // a connection that's still being negotiated is in use, so it's left alone
+        let Some(mut current) = self.current.try_lock() else {
+            return;
+        };
+
+        let expired = current
+            .as_ref()
+            .is_some_and(|connection| !connection.is_open() || connection.is_idle(idle_timeout));
+
+        if expired {
+            if let Some(connection) = current.take() {
+                connection.close();
+            }
+        }
+    }
+}
+
+impl<S> fmt::Debug for Multiplexer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Multiplexer");
+
+        match self.current.try_lock() {
+            Some(current) => debug.field("connection", &*current),
+            None => debug.field("connection", &format_args!("(negotiating)")),
+        };
+
+        debug.finish_non_exhaustive()
+    }
+}
+
+/// A connection shared by concurrent sessions, whose replies are routed to each session by a background task.
+pub(super) struct SharedConnection<S> {
+    /// The write half of the connection, which is locked while each packet is written so packets aren't interleaved.
+    writer: Mutex<WriteHalf<S>>,
+
+    /// The sessions waiting on replies from this connection.
+    sessions: StdMutex<Sessions>,
+}
+
+struct Sessions {
+    /// Channels to each in-flight session on the connection, keyed by session ID.
+    replies: HashMap<u32, mpsc::UnboundedSender<Vec<u8>>>,
+
+    /// Stops the reader task of the connection when sent or dropped, or `None` if the connection has been closed.
+    shutdown: Option<oneshot::Sender<()>>,
+
+    /// When a session was last attached to or received a packet from the connection, which is used to enforce the idle timeout.
+    last_active: Instant,
+}
+
+impl<S> SharedConnection<S> {
+    fn sessions(&self) -> std::sync::MutexGuard<'_, Sessions> {
+        // the map of sessions is always left in a valid state, so poisoning is ignored
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_open(&self) -> bool {
+        self.sessions().shutdown.is_some()
+    }
+
+    /// Whether this connection has had no sessions in flight for longer than the provided idle timeout.
+    fn is_idle(&self, idle_timeout: Option<Duration>) -> bool {
+        let sessions = self.sessions();
+        sessions.replies.is_empty()
+            && idle_timeout.is_some_and(|timeout| sessions.last_active.elapsed() >= timeout)
+    }
+
+    /// Marks this connection as active, e.g. when a session is attached to it.
+    fn touch(&self) {
+        self.sessions().last_active = Instant::now();
+    }
+
+    /// Registers a session on this connection, picking a new ID for it if the provided one is already in use.
+    fn register(
+        &self,
+        session_id: u32,
+    ) -> Result<(u32, mpsc::UnboundedReceiver<Vec<u8>>), ClientError> {
+        let mut sessions = self.sessions();
+        if sessions.shutdown.is_none() {
+            return Err(connection_closed().into());
+        }
+
+        // replies are routed by session ID, so every in-flight session on a connection must have a unique one
+        let mut session_id = session_id;
+        while sessions.replies.contains_key(&session_id) {
+            session_id = rand::thread_rng().gen();
+        }
+
+        let (sender, receiver) = mpsc::unbounded();
+        sessions.replies.insert(session_id, sender);
+
+        Ok((session_id, receiver))
+    }
+
+    fn unregister(&self, session_id: u32) {
+        let mut sessions = self.sessions();
+        sessions.replies.remove(&session_id);
+        sessions.last_active = Instant::now();
+    }
+
+    /// Passes a packet to the session it belongs to, if that session is still waiting on replies.
+    fn route(&self, session_id: u32, packet: Vec<u8>) {
+        let mut sessions = self.sessions();
+        sessions.last_active = Instant::now();
+
+        if let Some(session) = sessions.replies.get(&session_id) {
+            // the receiving session may have ended in the meantime, in which case the packet is dropped
+            let _ = session.unbounded_send(packet);
+        }
+    }
+
+    /// Marks this connection as closed, which stops its reader task and fails all sessions waiting on replies.
+    fn close(&self) {
+        let mut sessions = self.sessions();
+        sessions.replies.clear();
+        sessions.shutdown = None;
+    }
+}
+
+impl<S: AsyncWrite + Unpin> SharedConnection<S> {
+    /// Writes a serialized packet to the connection.
+    async fn write(&self, packet: &[u8]) -> io::Result<()> {
+        let mut writer = self.writer.lock().await;
+        if !self.is_open() {
+            return Err(connection_closed());
+        }
+
+        // a partially written packet would corrupt the stream for every session on this connection,
+        // so it's closed if writing fails or is cancelled partway through
+        let mut close_guard = CloseGuard {
+            connection: self,
+            armed: true,
+        };
+
+        writer.write_all(packet).await?;
+        writer.flush().await?;
+
+        close_guard.armed = false;
+        Ok(())
+    }
+}
+
+impl<S> fmt::Debug for SharedConnection<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sessions = self.sessions();
+
+        f.debug_struct("SharedConnection")
+            .field("open", &sessions.shutdown.is_some())
+            .field("sessions", &sessions.replies.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Closes a connection when dropped, unless disarmed first.
+struct CloseGuard<'connection, S> {
+    connection: &'connection SharedConnection<S>,
+    armed: bool,
+}
+
+impl<S> Drop for CloseGuard<'_, S> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.connection.close();
+        }
+    }
+}
+
+/// Reads packets from a connection and routes them to their sessions, until the connection is closed.
+async fn read_replies<S: AsyncRead>(
+    mut reader: ReadHalf<S>,
+    connection: Weak<SharedConnection<S>>,
+    shutdown: oneshot::Receiver<()>,
+) {
+    let routing = async {
+        let mut decoder = PacketDecoder::new(Vec::new());
+        let mut chunk = Vec::new();
+
+        loop {
+            // read exactly as much as the decoder needs, so packets are read one at a time
+            while !decoder.is_complete() {
+                chunk.resize(decoder.bytes_needed(), 0);
+                reader.read_exact(&mut chunk).await?;
+                decoder.feed(&chunk)?;
+            }
+
+            // SAFETY: the loop above only terminates once the decoder has a complete frame
+            let frame = decoder.take_frame().unwrap();
+
+            // the session ID isn't obfuscated, so it can be read before a packet is passed to its session
+            let header = HeaderInfo::try_from(&frame[..])?;
+
+            match connection.upgrade() {
+                Some(connection) => connection.route(header.session_id(), frame.to_vec()),
+                None => return Ok(()),
+            }
+        }
+    };
+
+    // reading stops on EOF, an error, or the connection being closed by a session
+    let _: Result<(), ClientError> = match future::select(pin!(routing), shutdown).await {
+        future::Either::Left((result, _)) => result,
+        future::Either::Right(_) => Ok(()),
+    };
+
+    // any sessions still waiting on a reply won't get one
+    if let Some(connection) = connection.upgrade() {
+        connection.close();
+    }
+}
+
+/// A session on a multiplexed connection, which mirrors the interface of [`ClientInner`](super::inner::ClientInner).
+pub(super) struct MultiplexedSession<'multiplexer, S> {
+    /// The multiplexer this session was started from.
+    multiplexer: &'multiplexer Multiplexer<S>,
+
+    /// The connection this session runs on, once it's been attached to one.
+    connection: Option<Arc<SharedConnection<S>>>,
+
+    /// The lock on the multiplexer's current connection, if this is the first session on a new connection
+    /// and its first reply hasn't arrived yet.
+    negotiation: Option<MutexGuard<'multiplexer, Option<Arc<SharedConnection<S>>>>>,
+
+    /// Whether the connection of this session is shared with other sessions, i.e. single connection mode was established.
+    shared: bool,
+
+    /// The ID this session was registered with on its connection, along with the channel its replies are received from.
+    registration: Option<(u32, mpsc::UnboundedReceiver<Vec<u8>>)>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> MultiplexedSession<'_, S> {
+    /// Attaches this session to the multiplexer's current connection, opening a new one if necessary.
+    async fn connection(
+        &mut self,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<Arc<SharedConnection<S>>, ClientError> {
+        if let Some(connection) = &self.connection {
+            return Ok(Arc::clone(connection));
+        }
+
+        let mut current = self.multiplexer.current.lock().await;
+        let idle_timeout = timeouts.and_then(|settings| settings.timeouts().idle);
+
+        // a connection that was closed (e.g., by the server) or has been idle for too long is replaced by a new one
+        let reusable = current
+            .as_ref()
+            .filter(|current| current.is_open() && !current.is_idle(idle_timeout));
+
+        let connection = match reusable {
+            Some(connection) => {
+                // the connection isn't idle anymore, even though this session hasn't registered on it yet
+                connection.touch();
+                self.shared = true;
+                Arc::clone(connection)
+            }
+            None => {
+                // an idle connection has no sessions in flight, so it can be closed without affecting any
+                if let Some(previous) = current.take() {
+                    previous.close();
+                }
+
+                let connection = self.multiplexer.connect(timeouts).await?;
+
+                // other sessions wait for this one's first reply to find out if they can share its connection
+                self.negotiation = Some(current);
+                connection
+            }
+        };
+
+        self.connection = Some(Arc::clone(&connection));
+        Ok(connection)
+    }
+
+    /// Writes a packet to the connection of this session, attaching to one if necessary.
+    ///
+    /// The first packet of a session is sent with a different session ID if its original one is already in use
+    /// by another session on the same connection, so the header of the sent packet is returned.
+    pub(super) async fn send_packet<B: PacketBody + Serialize + Clone>(
+        &mut self,
+        packet: Packet<B>,
+        secret_key: Option<&[u8]>,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<HeaderInfo, ClientError> {
+        let connection = self.connection(timeouts).await?;
+
+        let packet = match self.registration {
+            Some(_) => packet,
+            None => {
+                let header = *packet.header();
+                let (session_id, replies) = connection.register(header.session_id())?;
+                self.registration = Some((session_id, replies));
+
+                if session_id == header.session_id() {
+                    packet
+                } else {
+                    let header = HeaderInfo::new(
+                        header.version(),
+                        header.sequence_number(),
+                        header.flags(),
+                        session_id,
+                    );
+                    Packet::new(header, packet.body().clone())
+                }
+            }
+        };
+
+        let header = *packet.header();
+        let packet_buffer = inner::serialize_packet(packet, secret_key)?;
+        connection.write(&packet_buffer).await?;
+
+        Ok(header)
+    }
+
+    /// Waits for the next packet sent to this session.
+    pub(super) async fn receive_packet<B>(
+        &mut self,
+        secret_key: Option<&[u8]>,
+        expected_sequence_number: u8,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<Packet<B>, ClientError>
+    where
+        B: PacketBody + for<'a> Deserialize<'a>,
+    {
+        // a session only receives replies once it's sent a packet
+        let Some((_, replies)) = self.registration.as_mut() else {
+            return Err(connection_closed().into());
+        };
+
+        // the channel is closed along with the connection
+        let mut packet = timeout::limit(timeouts, |timeouts| timeouts.read, async {
+            replies.next().await.ok_or_else(connection_closed)
+        })
+        .await?;
+
+        inner::deserialize_packet(&mut packet, secret_key, expected_sequence_number)
+    }
+
+    /// Lets other sessions share the connection of this one if the server agreed to single connection mode in its first reply.
+    pub(super) fn set_internal_single_connect_status(&mut self, header: &HeaderInfo) {
+        // only the first reply of the first session on a connection negotiates single connection mode (RFC8907 section 4.3)
+        if let Some(mut current) = self.negotiation.take() {
+            if header.sequence_number() == 2
+                && header.flags().contains(PacketFlags::SINGLE_CONNECTION)
+            {
+                *current = self.connection.clone();
+                self.shared = true;
+            }
+        }
+    }
+
+    /// Stops this session from using its connection after an error.
+    ///
+    /// Errors affecting the connection itself close it for all sessions anyway, so a shared connection is
+    /// left open for the other sessions on it. It's retired regardless, since it may have stopped working
+    /// (e.g., if the session timed out), so new sessions are started on a fresh one.
+    pub(super) async fn discard_connection(&mut self) {
+        self.retire_connection().await;
+
+        if let Some(connection) = self.detach() {
+            if !self.shared {
+                connection.close();
+            }
+        }
+    }
+
+    pub(super) async fn post_session_cleanup(&mut self, status_is_error: bool) -> io::Result<()> {
+        // an error doesn't affect other sessions on a shared connection, but new sessions are started on a fresh one
+        if status_is_error {
+            self.retire_connection().await;
+        }
+
+        // close connection if server doesn't agree to SINGLE_CONNECTION negotiation
+        if let Some(connection) = self.detach() {
+            if !self.shared {
+                connection.writer.lock().await.close().await?;
+                connection.close();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops new sessions from being started on the connection of this session.
+    async fn retire_connection(&mut self) {
+        let mut current = match self.negotiation.take() {
+            Some(current) => current,
+            None => self.multiplexer.current.lock().await,
+        };
+
+        let is_current = match (current.as_ref(), self.connection.as_ref()) {
+            (Some(current), Some(connection)) => Arc::ptr_eq(current, connection),
+            _ => false,
+        };
+
+        if is_current {
+            *current = None;
+        }
+    }
+}
+
+impl<S> MultiplexedSession<'_, S> {
+    /// Detaches this session from its connection, returning the connection if it was attached to one.
+    fn detach(&mut self) -> Option<Arc<SharedConnection<S>>> {
+        let connection = self.connection.take()?;
+
+        // late replies to a finished session are dropped by the reader task
+        if let Some((session_id, _)) = self.registration.take() {
+            connection.unregister(session_id);
+        }
+
+        Some(connection)
+    }
+}
+
+impl<S> Drop for MultiplexedSession<'_, S> {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+/// The error returned to sessions whose connection was closed while they were using it.
+fn connection_closed() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::ConnectionAborted,
+        "multiplexed connection was closed",
+    )
+}
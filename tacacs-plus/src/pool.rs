@@ -0,0 +1,188 @@
+//! A bounded pool of connections, which allows sessions to run in parallel.
+
+use std::fmt;
+use std::io;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future;
+use futures::lock::{Mutex, MutexGuard};
+use futures::{AsyncRead, AsyncWrite};
+use tacacs_plus_protocol::{Deserialize, PacketBody, Serialize};
+use tacacs_plus_protocol::{HeaderInfo, Packet};
+
+use super::inner::{ClientInner, SharedFactory};
+use super::multiplex::{MultiplexedSession, Multiplexer, Spawner};
+use super::timeout::TimeoutSettings;
+use super::ClientError;
+
+pub(super) struct ConnectionPool<S> {
+    /// The factory shared by all connections in the pool, which is kept around to create resized pools.
+    factory: SharedFactory<S>,
+
+    /// The connections sessions are run on.
+    connections: Connections<S>,
+}
+
+enum Connections<S> {
+    /// Connections that are each only used by one session at a time.
+    ///
+    /// Connections are opened lazily, so a slot only holds an open connection once a session has used it.
+    Exclusive(Vec<Mutex<ClientInner<S>>>),
+
+    /// Connections that are shared by concurrent sessions once single connection mode is established.
+    Multiplexed(Multiplexer<S>),
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> ConnectionPool<S> {
+    pub(super) fn new(factory: SharedFactory<S>, max_connections: NonZeroUsize) -> Self {
+        let connections = (0..max_connections.get())
+            .map(|_| Mutex::new(ClientInner::new(Arc::clone(&factory))))
+            .collect();
+
+        Self {
+            factory,
+            connections: Connections::Exclusive(connections),
+        }
+    }
+
+    pub(super) fn multiplexed(factory: SharedFactory<S>, spawner: Arc<dyn Spawner>) -> Self
+    where
+        S: Send + 'static,
+    {
+        let multiplexer = Multiplexer::new(Arc::clone(&factory), spawner);
+
+        Self {
+            factory,
+            connections: Connections::Multiplexed(multiplexer),
+        }
+    }
+
+    /// Returns the connection factory used by this pool.
+    pub(super) fn factory(&self) -> SharedFactory<S> {
+        Arc::clone(&self.factory)
+    }
+
+    /// Acquires a connection for the duration of a session, waiting for one to become available if all are in use.
+    pub(super) async fn acquire(&self) -> PooledConnection<'_, S> {
+        let connections = match &self.connections {
+            Connections::Exclusive(connections) => connections,
+            Connections::Multiplexed(multiplexer) => {
+                return PooledConnection::Multiplexed(multiplexer.session())
+            }
+        };
+
+        // connections are checked in order so sessions favor the first few, which are more likely to already be open
+        if let Some(connection) = connections.iter().find_map(Mutex::try_lock) {
+            return PooledConnection::Exclusive(connection);
+        }
+
+        // otherwise, wait for whichever connection is freed up first
+        let (connection, _, _) = future::select_all(connections.iter().map(Mutex::lock)).await;
+        PooledConnection::Exclusive(connection)
+    }
+
+    /// Closes connections that have exceeded the provided idle timeout or been closed by the server.
+    ///
+    /// Connections in use by a session are skipped.
+    pub(super) async fn check_liveness(&self, idle_timeout: Option<Duration>) {
+        match &self.connections {
+            Connections::Exclusive(connections) => {
+                for connection in connections {
+                    if let Some(mut inner) = connection.try_lock() {
+                        inner.check_liveness(idle_timeout).await;
+                    }
+                }
+            }
+            Connections::Multiplexed(multiplexer) => multiplexer.check_liveness(idle_timeout),
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for ConnectionPool<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let connections = match &self.connections {
+            Connections::Exclusive(connections) => connections,
+            Connections::Multiplexed(multiplexer) => return multiplexer.fmt(f),
+        };
+
+        let mut list = f.debug_list();
+
+        for connection in connections {
+            // adapted from std mutex impl
+            match connection.try_lock() {
+                Some(inner) => list.entry(&*inner),
+                None => list.entry(&format_args!("(locked)")),
+            };
+        }
+
+        list.finish()
+    }
+}
+
+/// A connection acquired from a [`ConnectionPool`] for the duration of a session.
+pub(super) enum PooledConnection<'pool, S> {
+    Exclusive(MutexGuard<'pool, ClientInner<S>>),
+    Multiplexed(MultiplexedSession<'pool, S>),
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> PooledConnection<'_, S> {
+    /// Writes a packet to the connection, returning the header it was sent with.
+    pub(super) async fn send_packet<B: PacketBody + Serialize + Clone>(
+        &mut self,
+        packet: Packet<B>,
+        secret_key: Option<&[u8]>,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<HeaderInfo, ClientError> {
+        match self {
+            Self::Exclusive(inner) => inner.send_packet(packet, secret_key, timeouts).await,
+            Self::Multiplexed(session) => session.send_packet(packet, secret_key, timeouts).await,
+        }
+    }
+
+    /// Receives the next packet of the session from the connection.
+    pub(super) async fn receive_packet<B>(
+        &mut self,
+        secret_key: Option<&[u8]>,
+        expected_sequence_number: u8,
+        timeouts: Option<&TimeoutSettings>,
+    ) -> Result<Packet<B>, ClientError>
+    where
+        B: PacketBody + for<'a> Deserialize<'a>,
+    {
+        match self {
+            Self::Exclusive(inner) => {
+                inner
+                    .receive_packet(secret_key, expected_sequence_number, timeouts)
+                    .await
+            }
+            Self::Multiplexed(session) => {
+                session
+                    .receive_packet(secret_key, expected_sequence_number, timeouts)
+                    .await
+            }
+        }
+    }
+
+    pub(super) fn set_internal_single_connect_status(&mut self, header: &HeaderInfo) {
+        match self {
+            Self::Exclusive(inner) => inner.set_internal_single_connect_status(header),
+            Self::Multiplexed(session) => session.set_internal_single_connect_status(header),
+        }
+    }
+
+    pub(super) async fn discard_connection(&mut self) {
+        match self {
+            Self::Exclusive(inner) => inner.discard_connection(),
+            Self::Multiplexed(session) => session.discard_connection().await,
+        }
+    }
+
+    pub(super) async fn post_session_cleanup(&mut self, status_is_error: bool) -> io::Result<()> {
+        match self {
+            Self::Exclusive(inner) => inner.post_session_cleanup(status_is_error).await,
+            Self::Multiplexed(session) => session.post_session_cleanup(status_is_error).await,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Prompting for user input during interactive (ASCII) authentication sessions.
+
+use std::future::{self, Future};
+use std::pin::Pin;
+
+#[cfg(test)]
+mod tests;
+
+/// The kind of input requested by a server during an interactive authentication session.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PromptKind {
+    /// The server requested a username (`GETUSER`).
+    Username,
+
+    /// The server requested a password (`GETPASS`).
+    Password,
+
+    /// The server requested some other input, e.g. a one-time code (`GETDATA`).
+    Data,
+}
+
+/// A request for input from a server during an interactive authentication session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Prompt<'message> {
+    /// The kind of input the server requested.
+    pub kind: PromptKind,
+
+    /// The message sent by the server, which is intended to be displayed to the user (e.g., `Password: `).
+    pub message: &'message str,
+
+    /// Whether the user's input should be hidden as it's entered, e.g. for a password.
+    ///
+    /// This corresponds to the [`NO_ECHO`](tacacs_plus_protocol::authentication::ReplyFlags::NO_ECHO) flag
+    /// being set in the server's reply.
+    pub no_echo: bool,
+}
+
+/// The response to a [`Prompt`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PromptResponse {
+    /// The input to send to the server.
+    Answer(String),
+
+    /// Aborts the authentication session, optionally with a reason that is sent to the server.
+    Abort(Option<String>),
+}
+
+/// A (pinned, boxed) future that resolves to the response to a [`Prompt`].
+pub type PromptFuture<'prompt> = Pin<Box<dyn Future<Output = PromptResponse> + Send + 'prompt>>;
+
+/// A source of user input for interactive authentication sessions, e.g. a terminal.
+///
+/// # Examples
+///
+/// ```
+/// use tacacs_plus::{Prompt, PromptFuture, PromptKind, PromptResponse, Prompter};
+///
+/// /// Answers prompts with credentials known ahead of time.
+/// struct StoredCredentials {
+///     password: String,
+/// }
+///
+/// impl Prompter for StoredCredentials {
+///     fn prompt<'prompt>(&'prompt mut self, prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+///         Box::pin(async move {
+///             match prompt.kind {
+///                 PromptKind::Password => PromptResponse::Answer(self.password.clone()),
+///                 _ => PromptResponse::Abort(Some(String::from("unsupported prompt"))),
+///             }
+///         })
+///     }
+/// }
+/// ```
+pub trait Prompter {
+    /// Asks for the input requested by the server, or for the session to be aborted.
+    fn prompt<'prompt>(&'prompt mut self, prompt: Prompt<'prompt>) -> PromptFuture<'prompt>;
+}
+
+/// A prompter that answers with a fixed username and password, used for non-interactive ASCII authentication.
+pub(super) struct PasswordPrompter<'credentials> {
+    user: &'credentials str,
+    password: &'credentials str,
+}
+
+impl<'credentials> PasswordPrompter<'credentials> {
+    pub(super) fn new(user: &'credentials str, password: &'credentials str) -> Self {
+        Self { user, password }
+    }
+}
+
+impl Prompter for PasswordPrompter<'_> {
+    fn prompt<'prompt>(&'prompt mut self, prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        let response = match prompt.kind {
+            PromptKind::Username => PromptResponse::Answer(self.user.to_owned()),
+            PromptKind::Password => PromptResponse::Answer(self.password.to_owned()),
+
+            // there's no way to know what other data a server might want
+            PromptKind::Data => PromptResponse::Abort(Some(String::from(
+                "only a username and password can be provided",
+            ))),
+        };
+
+        Box::pin(future::ready(response))
+    }
+}
+
+/// A prompter that answers with a username along with an old and new password, used for non-interactive password changes.
+///
+/// The first password (or data) prompt is answered with the old password, and all later ones with the new password.
+pub(super) struct ChangePasswordPrompter<'credentials> {
+    user: &'credentials str,
+    old_password: &'credentials str,
+    new_password: &'credentials str,
+    old_password_sent: bool,
+}
+
+impl<'credentials> ChangePasswordPrompter<'credentials> {
+    pub(super) fn new(
+        user: &'credentials str,
+        old_password: &'credentials str,
+        new_password: &'credentials str,
+    ) -> Self {
+        Self {
+            user,
+            old_password,
+            new_password,
+            old_password_sent: false,
+        }
+    }
+}
+
+impl Prompter for ChangePasswordPrompter<'_> {
+    fn prompt<'prompt>(&'prompt mut self, prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        let answer = match prompt.kind {
+            PromptKind::Username => self.user,
+
+            // servers may use either GETPASS or GETDATA for passwords
+            PromptKind::Password | PromptKind::Data if !self.old_password_sent => {
+                self.old_password_sent = true;
+                self.old_password
+            }
+            PromptKind::Password | PromptKind::Data => self.new_password,
+        };
+
+        Box::pin(future::ready(PromptResponse::Answer(answer.to_owned())))
+    }
+}
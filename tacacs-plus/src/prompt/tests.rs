@@ -0,0 +1,46 @@
+use super::{ChangePasswordPrompter, Prompt, PromptKind, PromptResponse, Prompter};
+
+async fn answer<P: Prompter>(prompter: &mut P, kind: PromptKind) -> PromptResponse {
+    prompter
+        .prompt(Prompt {
+            kind,
+            message: "",
+            no_echo: kind != PromptKind::Username,
+        })
+        .await
+}
+
+#[tokio::test]
+async fn change_password_prompts() {
+    let mut prompter = ChangePasswordPrompter::new("someuser", "old", "new");
+
+    let expected = [
+        (PromptKind::Username, "someuser"),
+        (PromptKind::Password, "old"),
+        (PromptKind::Data, "new"),
+        (PromptKind::Password, "new"),
+    ];
+
+    for (kind, expected_answer) in expected {
+        assert_eq!(
+            answer(&mut prompter, kind).await,
+            PromptResponse::Answer(expected_answer.to_owned()),
+            "wrong answer to {kind:?} prompt"
+        );
+    }
+}
+
+#[tokio::test]
+async fn change_password_old_password_as_data() {
+    let mut prompter = ChangePasswordPrompter::new("someuser", "old", "new");
+
+    // the old password is sent for the first password prompt, even if it's a GETDATA
+    assert_eq!(
+        answer(&mut prompter, PromptKind::Data).await,
+        PromptResponse::Answer("old".to_owned())
+    );
+    assert_eq!(
+        answer(&mut prompter, PromptKind::Password).await,
+        PromptResponse::Answer("new".to_owned())
+    );
+}
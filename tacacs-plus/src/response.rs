@@ -1,5 +1,7 @@
-use tacacs_plus_protocol::Argument;
 use tacacs_plus_protocol::{authentication, authorization};
+use tacacs_plus_protocol::{Argument, PrivilegeLevel};
+
+use crate::AuthenticationType;
 
 /// The final status returned by a server during a TACACS+ session.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -22,6 +24,59 @@ pub struct AuthenticationResponse {
 
     /// Extra data returned by the server.
     pub data: Vec<u8>,
+
+    /// The credentials returned by the server for outbound authentication, if this was a successful
+    /// [`send_auth()`](crate::Client::send_auth) session.
+    pub outbound_credentials: Option<OutboundCredentials>,
+
+    /// The authentication types the server will accept instead, if it asked for the session to be restarted.
+    ///
+    /// Types are listed in the order sent by the server, and any that aren't supported by this client are omitted.
+    /// The list may also be empty, since servers aren't required to include one.
+    ///
+    /// See [`ContextBuilder::allow_restart()`](crate::ContextBuilder::allow_restart) for restarting
+    /// sessions automatically.
+    pub restart_types: Option<Vec<AuthenticationType>>,
+}
+
+/// Credentials returned by a server for authenticating to a remote peer, as requested via
+/// [`Client::send_auth()`](crate::Client::send_auth).
+#[non_exhaustive]
+#[derive(PartialEq, Eq, Debug, Clone, Hash)]
+pub enum OutboundCredentials {
+    /// A password to send to the peer via PAP.
+    Password(Vec<u8>),
+
+    /// The response to send to the peer's CHAP challenge.
+    ChapResponse([u8; 16]),
+}
+
+/// A server response from an enable (privilege escalation) authentication session.
+#[must_use = "A denied privilege level is not reported as an error, so the status field must be checked."]
+#[derive(PartialEq, Eq, Debug, Clone, Hash)]
+pub struct EnableResponse {
+    /// Whether the requested privilege level was granted.
+    pub status: ResponseStatus,
+
+    /// The privilege level that was requested.
+    pub privilege_level: PrivilegeLevel,
+
+    /// The message returned by the server, intended to be displayed to the user.
+    pub user_message: String,
+
+    /// Extra data returned by the server.
+    pub data: Vec<u8>,
+}
+
+impl EnableResponse {
+    pub(super) fn new(response: AuthenticationResponse, privilege_level: PrivilegeLevel) -> Self {
+        Self {
+            status: response.status,
+            privilege_level,
+            user_message: response.user_message,
+            data: response.data,
+        }
+    }
 }
 
 /// A TACACS+ server response from an authorization session.
@@ -68,7 +123,8 @@ impl TryFrom<authentication::Status> for ResponseStatus {
             #[allow(deprecated)]
             authentication::Status::Follow => Ok(ResponseStatus::Failure),
 
-            // we don't support restart status for now, so we treat it as a failure per RFC 8907
+            // a restart that isn't honored is treated as a failure per RFC 8907, with the types the server
+            // will accept reported separately in the response
             // (see section 5.4.3 of RFC 8907: https://www.rfc-editor.org/rfc/rfc8907.html#section-5.4.3-6)
             authentication::Status::Restart => Ok(ResponseStatus::Failure),
 
@@ -0,0 +1,115 @@
+//! Retrying sessions that fail due to transport errors.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rand::Rng;
+
+use super::Timer;
+
+#[cfg(test)]
+mod tests;
+
+/// A policy for retrying sessions that fail due to transport errors, i.e. [`ClientError::IOError`](super::ClientError::IOError)s
+/// and [`ClientError::Timeout`](super::ClientError::Timeout)s.
+///
+/// A session is only retried if no reply was received from the server, so a definitive response (e.g., an
+/// authentication failure) is never retried. Each attempt uses a new connection, and consecutive attempts are
+/// separated by an exponentially increasing backoff.
+///
+/// ASCII authentication sessions with a caller-supplied [`Prompter`](super::Prompter) (e.g., from
+/// [`authenticate_interactive()`](super::Client::authenticate_interactive)) are never retried, since a prompter isn't expected
+/// to be used for more than one session. Those started by [`authenticate()`](super::Client::authenticate) or
+/// [`change_password()`](super::Client::change_password), whose answers are known up front, are retried like any other session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts for a session, including the first one.
+    ///
+    /// A value of 0 is treated the same as 1, i.e. sessions are not retried.
+    pub max_attempts: u32,
+
+    /// The backoff before the first retry, which is doubled for each subsequent one.
+    pub initial_backoff: Duration,
+
+    /// The maximum backoff between attempts.
+    pub max_backoff: Duration,
+
+    /// Whether to randomize each backoff, picking a duration between half of and the full calculated value.
+    ///
+    /// This avoids many clients retrying in lockstep after a server outage.
+    pub jitter: bool,
+
+    /// Whether accounting requests are retried as well.
+    ///
+    /// This is disabled by default, since accounting isn't idempotent; a server may have recorded a request even if
+    /// its reply was lost, in which case a retry produces a duplicate record.
+    pub retry_accounting: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(2),
+            jitter: true,
+            retry_accounting: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Calculates the backoff before the provided retry, where the first retry (i.e., second attempt) is number 1.
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            rand::thread_rng().gen_range(backoff / 2..=backoff)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// The retry policy configured for a client, along with the timer used to wait between attempts.
+#[derive(Clone)]
+pub(super) struct RetrySettings {
+    timer: Arc<dyn Timer>,
+    policy: RetryPolicy,
+}
+
+impl RetrySettings {
+    pub(super) fn new<T: Timer + 'static>(timer: T, policy: RetryPolicy) -> Self {
+        Self {
+            timer: Arc::new(timer),
+            policy,
+        }
+    }
+
+    /// Returns the maximum number of attempts for a session, depending on whether it's an accounting session.
+    pub(super) fn max_attempts(&self, accounting: bool) -> u32 {
+        if accounting && !self.policy.retry_accounting {
+            1
+        } else {
+            self.policy.max_attempts.max(1)
+        }
+    }
+
+    /// Waits out the backoff before the provided retry.
+    pub(super) async fn backoff(&self, retry: u32) {
+        self.timer.sleep(self.policy.backoff(retry)).await;
+    }
+}
+
+impl fmt::Debug for RetrySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetrySettings")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
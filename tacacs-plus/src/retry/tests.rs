@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use super::RetryPolicy;
+
+fn policy(jitter: bool) -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_secs(1),
+        jitter,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn backoff_doubles() {
+    let policy = policy(false);
+
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(400));
+    assert_eq!(policy.backoff(4), Duration::from_millis(800));
+}
+
+#[test]
+fn backoff_capped() {
+    let policy = policy(false);
+
+    assert_eq!(policy.backoff(5), Duration::from_secs(1));
+    assert_eq!(policy.backoff(40), Duration::from_secs(1));
+    assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+}
+
+#[test]
+fn backoff_jitter_range() {
+    let policy = policy(true);
+
+    for (retry, full) in [(1, 100), (2, 200), (3, 400), (4, 800), (10, 1000)] {
+        let full = Duration::from_millis(full);
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(retry);
+            assert!(
+                (full / 2..=full).contains(&backoff),
+                "backoff {backoff:?} for retry {retry} not between {:?} and {full:?}",
+                full / 2
+            );
+        }
+    }
+}
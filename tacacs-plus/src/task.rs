@@ -5,12 +5,10 @@ use futures::{AsyncRead, AsyncWrite};
 use tacacs_plus_protocol::accounting::{Flags, ReplyOwned, Request, Status};
 use tacacs_plus_protocol::Packet;
 use tacacs_plus_protocol::{Argument, Arguments, FieldText};
-use tacacs_plus_protocol::{
-    AuthenticationContext, AuthenticationService, AuthenticationType, MinorVersion,
-};
+use tacacs_plus_protocol::{AuthenticationContext, MinorVersion};
 
 use super::response::AccountingResponse;
-use super::{Client, ClientError, SessionContext};
+use super::{Client, ClientError, SessionContext, SessionProgress};
 
 // Arguments specified in RFC8907 section 8.3.
 /// Task ID, used for grouping together records from the same task.
@@ -57,12 +55,15 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
     /// Note that setting `start_time` requires the system clock to be set after the Unix epoch; otherwise,
     /// an error is returned.
     ///
-    /// This method should only be called once per task.
+    /// This method should only be called once per task. How far the session with the server got is returned as well.
     pub(super) async fn start<'args, A: AsRef<[Argument<'args>]>>(
         client: &'a Client<S>,
         context: SessionContext,
         arguments: A,
-    ) -> Result<(Self, AccountingResponse), ClientError> {
+    ) -> (
+        Result<(Self, AccountingResponse), ClientError>,
+        SessionProgress,
+    ) {
         let task = Self {
             client,
             id: uuid::Uuid::new_v4().to_string(),
@@ -70,12 +71,28 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
             start_time: Instant::now(),
         };
 
+        let full_arguments = match task.start_arguments(arguments.as_ref()) {
+            Ok(arguments) => arguments,
+            Err(error) => return (Err(error), SessionProgress::Unsent),
+        };
+
+        // perform accounting request with task info/arguments
+        let (response, progress) = task.make_request(Flags::StartRecord, full_arguments).await;
+
+        (response.map(|response| (task, response)), progress)
+    }
+
+    /// Returns the arguments of a start record, i.e. the provided ones preceded by the task ID & start time.
+    fn start_arguments<'args>(
+        &'args self,
+        arguments: &[Argument<'args>],
+    ) -> Result<Vec<Argument<'args>>, ClientError> {
         // prepend a couple of informational arguments specified in RFC 8907 section 8.3
         let mut full_arguments = vec![
             Argument::new(
                 // SAFETY: both fields are known to always be valid ASCII (hardcoded/UUID)
                 FieldText::try_from(TASK_ID).unwrap(),
-                FieldText::try_from(&*task.id).unwrap(),
+                FieldText::try_from(&*self.id).unwrap(),
                 true,
             )?,
             Argument::new(
@@ -85,14 +102,9 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
                 true,
             )?,
         ];
-        full_arguments.extend_from_slice(arguments.as_ref());
-
-        // perform accounting request with task info/arguments
-        let response = task
-            .make_request(Flags::StartRecord, full_arguments)
-            .await?;
+        full_arguments.extend_from_slice(arguments);
 
-        Ok((task, response))
+        Ok(full_arguments)
     }
 
     /// Sends an update to the TACACS+ server about this task with the provided arguments.
@@ -123,6 +135,7 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
 
         self.make_request(Flags::WatchdogUpdate, full_arguments)
             .await
+            .0
     }
 
     /// Signals to the TACACS+ server that this task has completed.
@@ -155,49 +168,29 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
         ];
         full_arguments.extend_from_slice(arguments.as_ref());
 
-        self.make_request(Flags::StopRecord, full_arguments).await
+        self.make_request(Flags::StopRecord, full_arguments).await.0
     }
 
+    /// Sends an accounting record with the provided flags & arguments, additionally returning how far the session got.
     async fn make_request(
         &self,
         flags: Flags,
         arguments: Vec<Argument<'_>>,
-    ) -> Result<AccountingResponse, ClientError> {
-        // send accounting request & ensure reply ok
-        let request_packet = Packet::new(
-            self.client.make_header(1, MinorVersion::Default),
-            Request::new(
-                flags,
-                self.context.authentication_method(),
-                AuthenticationContext {
-                    privilege_level: self.context.privilege_level,
-                    authentication_type: AuthenticationType::NotSet,
-                    // TODO: should we allow externally setting this?
-                    service: AuthenticationService::Login,
-                },
-                self.context.as_user_information()?,
-                Arguments::new(&arguments).ok_or(ClientError::TooManyArguments)?,
-            ),
-        );
-
-        let reply = {
-            let secret_key = self.client.secret.as_deref();
-
-            let mut inner = self.client.inner.lock().await;
-            inner.send_packet(request_packet, secret_key).await?;
-
-            let reply: Packet<ReplyOwned> = inner.receive_packet(secret_key, 2).await?;
-
-            // update inner state based on response
-            inner.set_internal_single_connect_status(reply.header());
-            inner
-                .post_session_cleanup(reply.body().status == Status::Error)
-                .await?;
-
-            reply
+    ) -> (Result<AccountingResponse, ClientError>, SessionProgress) {
+        let request_packet = match self.request_packet(flags, &arguments) {
+            Ok(packet) => packet,
+            Err(error) => return (Err(error), SessionProgress::Unsent),
         };
 
-        match reply.body().status {
+        // send accounting request & ensure reply ok
+        let (reply, progress) = self
+            .client
+            .single_exchange(request_packet, true, |reply: &ReplyOwned| {
+                reply.status == Status::Error
+            })
+            .await;
+
+        let response = reply.and_then(|reply| match reply.body().status {
             Status::Success => Ok(AccountingResponse {
                 user_message: reply.body().server_message.clone(),
                 admin_message: reply.body().data.clone(),
@@ -209,6 +202,30 @@ impl<'a, S: AsyncRead + AsyncWrite + Unpin> AccountingTask<&'a Client<S>> {
                 user_message: reply.body().server_message.clone(),
                 admin_message: reply.body().data.clone(),
             }),
-        }
+        });
+
+        (response, progress)
+    }
+
+    /// Constructs an accounting request packet for this task.
+    fn request_packet<'packet>(
+        &'packet self,
+        flags: Flags,
+        arguments: &'packet Vec<Argument<'packet>>,
+    ) -> Result<Packet<Request<'packet>>, ClientError> {
+        Ok(Packet::new(
+            self.client.make_header(1, MinorVersion::Default),
+            Request::new(
+                flags,
+                self.context.authentication_method(),
+                AuthenticationContext {
+                    privilege_level: self.context.privilege_level,
+                    authentication_type: self.context.authentication_type(),
+                    service: self.context.authentication_service(),
+                },
+                self.context.as_user_information()?,
+                Arguments::new(arguments).ok_or(ClientError::TooManyArguments)?,
+            ),
+        ))
     }
 }
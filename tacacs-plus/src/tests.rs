@@ -0,0 +1,36 @@
+use super::{restart_types, AuthenticationType};
+
+#[test]
+fn restart_types_empty_data() {
+    assert_eq!(restart_types(&[]), []);
+}
+
+#[test]
+fn restart_types_preserves_order() {
+    // MS-CHAPv2, PAP, ASCII, CHAP, MS-CHAP
+    let types = restart_types(&[0x06, 0x02, 0x01, 0x03, 0x05]);
+
+    assert_eq!(
+        types,
+        [
+            AuthenticationType::MsChapV2,
+            AuthenticationType::Pap,
+            AuthenticationType::Ascii,
+            AuthenticationType::Chap,
+            AuthenticationType::MsChap,
+        ]
+    );
+}
+
+#[test]
+fn restart_types_not_set_skipped() {
+    let types = restart_types(&[0x00, 0x03, 0x00]);
+    assert_eq!(types, [AuthenticationType::Chap]);
+}
+
+#[test]
+fn restart_types_unknown_skipped() {
+    // 0x04 is ARAP, which isn't supported by the client
+    let types = restart_types(&[0x04, 0x02, 0x42, 0xff, 0x05]);
+    assert_eq!(types, [AuthenticationType::Pap, AuthenticationType::MsChap]);
+}
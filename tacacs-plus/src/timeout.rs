@@ -0,0 +1,116 @@
+//! Runtime-independent timeouts for client operations.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::{self, Either};
+
+use super::ClientError;
+
+/// A (pinned, boxed) future that completes after some amount of time, as returned by a [`Timer`].
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A source of delays, which allows a [`Client`](super::Client) to enforce timeouts without depending on a specific async runtime.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use tacacs_plus::{SleepFuture, Timer};
+///
+/// /// A timer backed by the Tokio runtime.
+/// struct TokioTimer;
+///
+/// impl Timer for TokioTimer {
+///     fn sleep(&self, duration: Duration) -> SleepFuture {
+///         Box::pin(tokio::time::sleep(duration))
+///     }
+/// }
+/// ```
+pub trait Timer: Send + Sync {
+    /// Returns a future that completes once the provided duration has elapsed.
+    fn sleep(&self, duration: Duration) -> SleepFuture;
+}
+
+/// Timeouts for operations performed by a [`Client`](super::Client), where `None` means an operation is not time-limited.
+///
+/// If an operation times out, a [`ClientError::Timeout`] is returned and the connection it was using is dropped
+/// rather than reused, since a reply might still arrive later on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Timeouts {
+    /// The maximum time to wait for a new connection to be opened by the client's connection factory.
+    pub connect: Option<Duration>,
+
+    /// The maximum time to wait for each packet from the server.
+    pub read: Option<Duration>,
+
+    /// The maximum time for an entire session, from sending the first packet to processing the final reply.
+    ///
+    /// For interactive authentication sessions, this includes time spent waiting on a [`Prompter`](super::Prompter).
+    pub session: Option<Duration>,
+
+    /// The maximum time a connection kept open in single connection mode can go unused before it's closed
+    /// rather than reused for another session.
+    ///
+    /// Firewalls and servers may drop idle connections without notifying the client, in which case a session
+    /// sent over one only fails once the read timeout elapses. Idle connections are closed before the next
+    /// session starts, or in the background by a [`Client::liveness_check()`](super::Client::liveness_check) task.
+    /// Unlike other timeouts, this never causes an operation to fail.
+    pub idle: Option<Duration>,
+}
+
+/// The timeouts configured for a client, along with the timer used to enforce them.
+#[derive(Clone)]
+pub(super) struct TimeoutSettings {
+    timer: Arc<dyn Timer>,
+    timeouts: Timeouts,
+}
+
+impl TimeoutSettings {
+    pub(super) fn new<T: Timer + 'static>(timer: T, timeouts: Timeouts) -> Self {
+        Self {
+            timer: Arc::new(timer),
+            timeouts,
+        }
+    }
+
+    pub(super) fn timeouts(&self) -> &Timeouts {
+        &self.timeouts
+    }
+}
+
+impl fmt::Debug for TimeoutSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutSettings")
+            .field("timeouts", &self.timeouts)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Runs a future to completion, returning [`ClientError::Timeout`] if it takes longer than the timeout
+/// chosen from the provided settings (if any).
+pub(super) async fn limit<T, E, F>(
+    settings: Option<&TimeoutSettings>,
+    timeout: impl FnOnce(&Timeouts) -> Option<Duration>,
+    future: F,
+) -> Result<T, ClientError>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<ClientError>,
+{
+    let sleep = settings.and_then(|settings| {
+        timeout(&settings.timeouts).map(|duration| settings.timer.sleep(duration))
+    });
+
+    match sleep {
+        Some(sleep) => match future::select(pin!(future), sleep).await {
+            Either::Left((result, _)) => result.map_err(Into::into),
+            Either::Right(((), _)) => Err(ClientError::Timeout),
+        },
+        None => future.await.map_err(Into::into),
+    }
+}
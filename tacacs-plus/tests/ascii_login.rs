@@ -0,0 +1,190 @@
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::Client as TacacsClient;
+use tacacs_plus::{AuthenticationType, ClientError, ContextBuilder, ResponseStatus};
+use tacacs_plus::{Prompt, PromptFuture, PromptKind, PromptResponse, Prompter};
+
+mod common;
+use common::fake_server::{self, status};
+
+type Client = TacacsClient<Compat<TcpStream>>;
+
+fn make_client() -> Client {
+    let address = common::get_server_address();
+    Client::new(
+        Box::new(move || {
+            TcpStream::connect(address.clone())
+                .map_ok(TokioAsyncWriteCompatExt::compat_write)
+                .boxed()
+        }),
+        Some(common::SECRET_KEY),
+    )
+}
+
+/// A prompter that answers with fixed credentials and records the prompts it received.
+struct RecordingPrompter {
+    user: String,
+    password: String,
+    prompts: Vec<(PromptKind, bool)>,
+}
+
+impl Prompter for RecordingPrompter {
+    fn prompt<'prompt>(&'prompt mut self, prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        self.prompts.push((prompt.kind, prompt.no_echo));
+
+        let answer = match prompt.kind {
+            PromptKind::Username => self.user.clone(),
+            _ => self.password.clone(),
+        };
+
+        Box::pin(async move { PromptResponse::Answer(answer) })
+    }
+}
+
+/// A prompter that aborts the session at the first prompt.
+struct AbortingPrompter;
+
+impl Prompter for AbortingPrompter {
+    fn prompt<'prompt>(&'prompt mut self, _prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        Box::pin(async { PromptResponse::Abort(Some(String::from("user cancelled login"))) })
+    }
+}
+
+/// A prompter that waits a while before answering with a password.
+struct SlowPrompter(Duration);
+
+impl Prompter for SlowPrompter {
+    fn prompt<'prompt>(&'prompt mut self, _prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        Box::pin(async move {
+            tokio::time::sleep(self.0).await;
+            PromptResponse::Answer("hunter2".to_owned())
+        })
+    }
+}
+
+#[tokio::test]
+async fn ascii_success() {
+    let client = make_client();
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Ascii)
+        .await
+        .expect("error completing ASCII authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+}
+
+#[tokio::test]
+async fn ascii_wrong_password() {
+    let client = make_client();
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "not the password", AuthenticationType::Ascii)
+        .await
+        .expect("error completing ASCII authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Failure,
+        "authentication should have failed with the wrong password"
+    );
+}
+
+#[tokio::test]
+async fn ascii_interactive_prompts() {
+    let client = make_client();
+
+    let mut prompter = RecordingPrompter {
+        user: "someuser".to_owned(),
+        password: "hunter2".to_owned(),
+        prompts: Vec::new(),
+    };
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate_interactive(context, &mut prompter)
+        .await
+        .expect("error completing interactive authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+
+    // the password should have been prompted for without echoing input
+    assert!(
+        prompter.prompts.contains(&(PromptKind::Password, true)),
+        "password prompt not received with NO_ECHO set, got prompts: {:?}",
+        prompter.prompts
+    );
+}
+
+#[tokio::test]
+async fn ascii_abort() {
+    let client = make_client();
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate_interactive(context, &mut AbortingPrompter)
+        .await
+        .expect_err("aborted session should return an error");
+    assert!(
+        matches!(error, ClientError::AuthenticationAborted),
+        "wrong error returned: {error:?}"
+    );
+
+    // the client should still be usable after an aborted session
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session after abort");
+    assert_eq!(response.status, ResponseStatus::Success);
+}
+
+#[tokio::test]
+async fn connection_closed_mid_session() {
+    let (address, connections) = fake_server::start(|mut stream| async move {
+        let start = fake_server::read_packet(&mut stream).await.unwrap();
+
+        // a continue sent on a new connection isn't part of any session
+        if start.sequence_number() != 1 {
+            return;
+        }
+
+        // the connection is closed as soon as the password prompt is sent
+        let reply = fake_server::authentication_reply(status::GETPASS, "Password: ", &[]);
+        fake_server::reply(&mut stream, &start, fake_server::UNENCRYPTED, &reply).await;
+    })
+    .await;
+
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    // the prompter waits long enough for the client to see that the server closed the connection
+    let prompter = &mut SlowPrompter(Duration::from_millis(100));
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate_interactive(context, prompter)
+        .await
+        .expect_err("session should have failed once the connection was closed");
+
+    assert!(
+        matches!(error, ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+
+    // the session wasn't continued on a new connection
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
@@ -3,7 +3,8 @@ use futures::FutureExt;
 
 use tacacs_plus::Argument;
 use tacacs_plus::Client;
-use tacacs_plus::{AuthenticationMethod, ConnectionFactory, ContextBuilder, ResponseStatus};
+use tacacs_plus::{AuthenticationMethod, AuthenticationService, AuthenticationType};
+use tacacs_plus::{ConnectionFactory, ContextBuilder, ResponseStatus};
 
 mod common;
 
@@ -147,3 +148,35 @@ async fn guest_authorize() {
         ]
     );
 }
+
+#[async_std::test]
+async fn authorize_with_authentication_details() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> =
+        Box::new(move || TcpStream::connect(address.clone()).boxed());
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    let arguments = vec![Argument::new(
+        "service".try_into().unwrap(),
+        "authorizeme".try_into().unwrap(),
+        true,
+    )
+    .unwrap()];
+
+    // as if the user had previously authenticated over PPP via PAP
+    let context = ContextBuilder::new("someuser".to_owned())
+        .auth_method(AuthenticationMethod::TacacsPlus)
+        .auth_type(AuthenticationType::Pap)
+        .auth_service(AuthenticationService::Ppp)
+        .build();
+    let response = client
+        .authorize(context, arguments)
+        .await
+        .expect("error when completing authorization session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authorization failed, full response: {response:?}"
+    );
+}
@@ -0,0 +1,91 @@
+use std::sync::{Arc, Mutex};
+
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+
+use tacacs_plus::{Client, ConnectionFactory, ContextBuilder, ResponseStatus};
+
+mod common;
+use common::fake_server::{self, status};
+
+// this test is ignored since neither of the test servers supports changing cleartext passwords
+#[tokio::test]
+#[ignore]
+async fn change_password_success() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    // the password is "changed" to the same value, so other tests aren't affected
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .change_password(context, "hunter2", "hunter2")
+        .await
+        .expect("error completing password change session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "password change failed, full response: {response:?}"
+    );
+}
+
+#[tokio::test]
+async fn change_password_exchange() {
+    let answers = Arc::new(Mutex::new(Vec::new()));
+
+    let server_answers = answers.clone();
+    let (address, _) = fake_server::start(move |mut stream| {
+        let answers = server_answers.clone();
+
+        async move {
+            let start = fake_server::read_packet(&mut stream).await.unwrap();
+            let (action, authentication_type, _, _) =
+                fake_server::authentication_start(&start.body);
+            // CHPASS action, ASCII authentication
+            assert_eq!((action, authentication_type), (0x02, 0x01));
+
+            let prompts = [
+                (status::GETUSER, "Username: "),
+                (status::GETPASS, "Old password: "),
+                (status::GETDATA, "New password: "),
+                (status::GETPASS, "Confirm new password: "),
+            ];
+
+            let mut request = start;
+            for (status, message) in prompts {
+                let reply = fake_server::authentication_reply(status, message, &[]);
+                fake_server::reply(&mut stream, &request, fake_server::UNENCRYPTED, &reply).await;
+
+                request = fake_server::read_packet(&mut stream).await.unwrap();
+                answers
+                    .lock()
+                    .unwrap()
+                    .push(fake_server::continue_message(&request.body));
+            }
+
+            let reply = fake_server::authentication_reply(status::PASS, "", &[]);
+            fake_server::reply(&mut stream, &request, fake_server::UNENCRYPTED, &reply).await;
+        }
+    })
+    .await;
+
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .change_password(context, "old password", "new password")
+        .await
+        .expect("error completing password change session");
+    assert_eq!(response.status, ResponseStatus::Success);
+
+    assert_eq!(
+        *answers.lock().unwrap(),
+        ["someuser", "old password", "new password", "new password"]
+    );
+}
@@ -1,8 +1,10 @@
 use futures::{FutureExt, TryFutureExt};
 use tokio_util::compat::TokioAsyncWriteCompatExt;
 
+use md5::{Digest, Md5};
+
 use tacacs_plus::{AuthenticationType, ConnectionFactory, ContextBuilder, ResponseStatus};
-use tacacs_plus::{Client, ClientError};
+use tacacs_plus::{ChapResponse, Client, ClientError};
 use tacacs_plus_protocol::DeserializeError;
 
 mod common;
@@ -47,6 +49,84 @@ async fn chap_failure() {
     );
 }
 
+/// Calculates a CHAP response the same way a PPP peer would.
+fn peer_chap_response(identifier: u8, secret: &str, challenge: &[u8]) -> [u8; 16] {
+    let mut hasher = Md5::new();
+    hasher.update([identifier]);
+    hasher.update(secret.as_bytes());
+    hasher.update(challenge);
+    hasher.finalize().into()
+}
+
+#[tokio::test]
+async fn chap_passthrough_success() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        tokio::net::TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    // values as they would be received from a PPP peer
+    let identifier = 42;
+    let challenge = b"challenge from the access server";
+    let response = peer_chap_response(identifier, "something different", challenge);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate_chap_response(
+            context,
+            ChapResponse {
+                identifier,
+                challenge,
+                response: &response,
+            },
+        )
+        .await
+        .expect("error completing CHAP pass-through session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+}
+
+#[tokio::test]
+async fn chap_passthrough_wrong_response() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        tokio::net::TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    // response calculated with a different identifier than the one relayed
+    let challenge = b"challenge from the access server";
+    let response = peer_chap_response(1, "something different", challenge);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate_chap_response(
+            context,
+            ChapResponse {
+                identifier: 2,
+                challenge,
+                response: &response,
+            },
+        )
+        .await
+        .expect("error completing CHAP pass-through session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Failure,
+        "authentication should have failed with a mismatched response"
+    );
+}
+
 #[tokio::test]
 async fn key_unconfigured() {
     let address = common::get_server_address();
@@ -0,0 +1,145 @@
+//! A minimal in-process TACACS+ server for testing exchanges that the test servers don't support.
+//!
+//! Packets are exchanged unobfuscated, so clients connecting to a fake server must not be given a secret key.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt, TryFutureExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
+
+use tacacs_plus::ConnectionFactory;
+
+/// The unencrypted header flag, which is set on all packets sent by a fake server.
+pub const UNENCRYPTED: u8 = 0x01;
+
+/// The single connection header flag.
+pub const SINGLE_CONNECTION: u8 = 0x04;
+
+/// A packet received by a fake server.
+#[derive(Debug, Clone)]
+pub struct RawPacket {
+    pub header: [u8; 12],
+    pub body: Vec<u8>,
+}
+
+impl RawPacket {
+    pub fn sequence_number(&self) -> u8 {
+        self.header[2]
+    }
+}
+
+/// Starts a fake server that handles each connection with the provided function, returning its address
+/// and a count of accepted connections.
+pub async fn start<F, Fut>(handler: F) -> (SocketAddr, Arc<AtomicUsize>)
+where
+    F: Fn(Compat<TcpStream>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.expect("couldn't accept connection");
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(handler(stream.compat()));
+        }
+    });
+
+    (address, connections)
+}
+
+/// Returns a connection factory that connects to the provided address.
+pub fn factory(address: SocketAddr) -> ConnectionFactory<Compat<TcpStream>> {
+    Box::new(move || {
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncReadCompatExt::compat)
+            .boxed()
+    })
+}
+
+/// Reads a packet, returning `None` if the connection was closed.
+pub async fn read_packet(stream: &mut Compat<TcpStream>) -> Option<RawPacket> {
+    let mut header = [0; 12];
+    stream.read_exact(&mut header).await.ok()?;
+
+    let body_length = u32::from_be_bytes(header[8..12].try_into().unwrap());
+    let mut body = vec![0; body_length as usize];
+    stream.read_exact(&mut body).await.ok()?;
+
+    Some(RawPacket { header, body })
+}
+
+/// Replies to a packet with the provided header flags and body.
+pub async fn reply(stream: &mut Compat<TcpStream>, request: &RawPacket, flags: u8, body: &[u8]) {
+    let mut reply = Vec::new();
+    // same version & type, next sequence number
+    reply.extend([
+        request.header[0],
+        request.header[1],
+        request.sequence_number() + 1,
+        flags,
+    ]);
+    // same session id
+    reply.extend(&request.header[4..8]);
+    reply.extend(u32::try_from(body.len()).unwrap().to_be_bytes());
+    reply.extend(body);
+
+    stream.write_all(&reply).await.unwrap();
+}
+
+/// Authentication reply statuses, as defined in RFC8907 section 5.2.
+pub mod status {
+    pub const PASS: u8 = 0x01;
+    pub const FAIL: u8 = 0x02;
+    pub const GETDATA: u8 = 0x03;
+    pub const GETUSER: u8 = 0x04;
+    pub const GETPASS: u8 = 0x05;
+    pub const RESTART: u8 = 0x06;
+    pub const ERROR: u8 = 0x07;
+}
+
+/// Encodes an authentication reply body with no flags.
+pub fn authentication_reply(status: u8, server_message: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = vec![status, 0];
+    body.extend(u16::try_from(server_message.len()).unwrap().to_be_bytes());
+    body.extend(u16::try_from(data.len()).unwrap().to_be_bytes());
+    body.extend(server_message.as_bytes());
+    body.extend(data);
+    body
+}
+
+/// Extracts the fields of an authentication start body, i.e. the action, authentication type, user and data.
+pub fn authentication_start(body: &[u8]) -> (u8, u8, String, Vec<u8>) {
+    let (user_length, port_length, remote_length, data_length) = (
+        body[4] as usize,
+        body[5] as usize,
+        body[6] as usize,
+        body[7] as usize,
+    );
+
+    let user_start = 8;
+    let data_start = user_start + user_length + port_length + remote_length;
+    let user = String::from_utf8(body[user_start..user_start + user_length].to_vec()).unwrap();
+
+    (
+        body[0],
+        body[2],
+        user,
+        body[data_start..data_start + data_length].to_vec(),
+    )
+}
+
+/// Extracts the user message of an authentication continue body.
+pub fn continue_message(body: &[u8]) -> String {
+    let message_length = u16::from_be_bytes([body[0], body[1]]) as usize;
+    String::from_utf8(body[5..5 + message_length].to_vec()).unwrap()
+}
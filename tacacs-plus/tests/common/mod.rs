@@ -1,3 +1,12 @@
+// each integration test only uses some of these helpers
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use tacacs_plus::{SleepFuture, Timer};
+
+pub mod fake_server;
+
 /// The TACACS+ secret key configured for integration tests.
 pub const SECRET_KEY: &str = "very secure key that is super secret";
 
@@ -8,3 +17,12 @@ const DEFAULT_ADDRESS: &str = "localhost:5555";
 pub fn get_server_address() -> String {
     std::env::var("TACACS_SERVER").unwrap_or(DEFAULT_ADDRESS.to_owned())
 }
+
+/// A [`Timer`] backed by tokio, for clients with timeouts or retries.
+pub struct TokioTimer;
+
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
@@ -0,0 +1,78 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt, TryFutureExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Barrier;
+use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::{AuthenticationType, Client, ConnectionFactory, ContextBuilder, ResponseStatus};
+
+/// Replies to a single unobfuscated authentication start with a PASS, after waiting at the provided barrier.
+async fn reply_after_barrier(stream: TcpStream, barrier: Arc<Barrier>) {
+    let mut stream = stream.compat();
+
+    let mut header = [0; 12];
+    stream.read_exact(&mut header).await.unwrap();
+
+    let body_length = u32::from_be_bytes(header[8..12].try_into().unwrap());
+    let mut body = vec![0; body_length as usize];
+    stream.read_exact(&mut body).await.unwrap();
+
+    // the reply is only sent once every session has sent its request
+    barrier.wait().await;
+
+    let mut reply = Vec::new();
+    // same version & type, sequence number 2, unencrypted flag set
+    reply.extend([header[0], header[1], 2, 0x01]);
+    // same session id
+    reply.extend(&header[4..8]);
+    reply.extend(6u32.to_be_bytes());
+    // PASS status, no flags, empty server message & data
+    reply.extend([0x01, 0, 0, 0, 0, 0]);
+
+    stream.write_all(&reply).await.unwrap();
+}
+
+#[tokio::test]
+async fn parallel_sessions() {
+    const SESSIONS: usize = 3;
+
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+    let barrier = Arc::new(Barrier::new(SESSIONS));
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.expect("couldn't accept connection");
+            tokio::spawn(reply_after_barrier(stream, barrier.clone()));
+        }
+    });
+
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, None::<&[u8]>)
+        .with_max_connections(NonZeroUsize::new(SESSIONS).unwrap());
+
+    let sessions = (0..SESSIONS).map(|_| {
+        let context = ContextBuilder::new("someuser".to_owned()).build();
+        client.authenticate(context, "hunter2", AuthenticationType::Pap)
+    });
+
+    // the sessions can only complete if they're all in progress at once
+    let responses =
+        tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(sessions))
+            .await
+            .expect("sessions should have run in parallel");
+
+    for response in responses {
+        let response = response.expect("error completing authentication session");
+        assert_eq!(response.status, ResponseStatus::Success);
+    }
+}
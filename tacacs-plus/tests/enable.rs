@@ -0,0 +1,66 @@
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::protocol::PrivilegeLevel;
+use tacacs_plus::Client as TacacsClient;
+use tacacs_plus::{AuthenticationType, ContextBuilder, ResponseStatus};
+
+mod common;
+
+type Client = TacacsClient<Compat<TcpStream>>;
+
+fn make_client() -> Client {
+    let address = common::get_server_address();
+    Client::new(
+        Box::new(move || {
+            TcpStream::connect(address.clone())
+                .map_ok(TokioAsyncWriteCompatExt::compat_write)
+                .boxed()
+        }),
+        Some(common::SECRET_KEY),
+    )
+}
+
+#[tokio::test]
+async fn enable_ascii_success() {
+    let client = make_client();
+
+    let privilege_level = PrivilegeLevel::new(15).unwrap();
+    let context = ContextBuilder::new("someuser".to_owned())
+        .privilege_level(privilege_level)
+        .build();
+
+    let response = client
+        .enable(context, "enablepass", AuthenticationType::Ascii)
+        .await
+        .expect("error completing enable session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "enable failed, full response: {response:?}"
+    );
+    assert_eq!(response.privilege_level, privilege_level);
+}
+
+#[tokio::test]
+async fn enable_wrong_password() {
+    let client = make_client();
+
+    let context = ContextBuilder::new("someuser".to_owned())
+        .privilege_level(PrivilegeLevel::new(15).unwrap())
+        .build();
+
+    // the login password shouldn't work for enable
+    let response = client
+        .enable(context, "hunter2", AuthenticationType::Ascii)
+        .await
+        .expect("error completing enable session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Failure,
+        "enable should have failed with the login password"
+    );
+}
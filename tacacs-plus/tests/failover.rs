@@ -0,0 +1,237 @@
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::{AuthenticationType, Client, ConnectionFactory, ContextBuilder};
+use tacacs_plus::{FailoverClient, ResponseStatus};
+
+mod common;
+use common::fake_server;
+
+/// Returns a factory for the test server, along with a counter of how many connections it has opened.
+fn counting_factory() -> (ConnectionFactory<Compat<TcpStream>>, Arc<AtomicUsize>) {
+    let address = common::get_server_address();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let counter = connections.clone();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+
+    (factory, connections)
+}
+
+/// A factory for a server that's down.
+fn unreachable_factory() -> ConnectionFactory<Compat<TcpStream>> {
+    Box::new(|| futures::future::err(io::Error::from(io::ErrorKind::ConnectionRefused)).boxed())
+}
+
+#[tokio::test]
+async fn fails_over_to_working_server() {
+    let (factory, connections) = counting_factory();
+    let client = FailoverClient::new(vec![
+        Client::new(unreachable_factory(), Some(common::SECRET_KEY)),
+        Client::new(factory, Some(common::SECRET_KEY)),
+    ])
+    .expect("server list should be non-empty");
+
+    for _ in 0..2 {
+        let context = ContextBuilder::new("someuser".to_owned()).build();
+        let response = client
+            .authenticate(context, "hunter2", AuthenticationType::Pap)
+            .await
+            .expect("error completing authentication session");
+
+        assert_eq!(
+            response.status,
+            ResponseStatus::Success,
+            "authentication failed, full response: {response:?}"
+        );
+    }
+
+    // the working server should be remembered for the second session
+    assert!(std::ptr::eq(client.last_working(), &client.servers()[1]));
+    assert!(connections.load(Ordering::Relaxed) >= 1);
+}
+
+#[tokio::test]
+async fn no_failover_on_failure() {
+    let (first_factory, _) = counting_factory();
+    let (second_factory, second_connections) = counting_factory();
+    let client = FailoverClient::new(vec![
+        Client::new(first_factory, Some(common::SECRET_KEY)),
+        Client::new(second_factory, Some(common::SECRET_KEY)),
+    ])
+    .expect("server list should be non-empty");
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "wrong password", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Failure);
+    assert_eq!(second_connections.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn all_servers_unreachable() {
+    let client = FailoverClient::new(vec![
+        Client::new(unreachable_factory(), Some(common::SECRET_KEY)),
+        Client::new(unreachable_factory(), Some(common::SECRET_KEY)),
+    ])
+    .expect("server list should be non-empty");
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect_err("authentication should have failed with no servers available");
+
+    assert!(
+        matches!(error, tacacs_plus::ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+}
+
+/// Starts a fake server that accepts every accounting record it receives.
+async fn accounting_server() -> (ConnectionFactory<Compat<TcpStream>>, Arc<AtomicUsize>) {
+    let (address, connections) = fake_server::start(|mut stream| async move {
+        while let Some(request) = fake_server::read_packet(&mut stream).await {
+            // empty server message & data, success status
+            let reply = [0, 0, 0, 0, 0x01];
+            fake_server::reply(&mut stream, &request, fake_server::UNENCRYPTED, &reply).await;
+        }
+    })
+    .await;
+
+    (fake_server::factory(address), connections)
+}
+
+/// Starts a fake server that reads a single request and then closes the connection without replying.
+async fn dropping_server() -> (ConnectionFactory<Compat<TcpStream>>, Arc<AtomicUsize>) {
+    let (address, connections) = fake_server::start(|mut stream| async move {
+        fake_server::read_packet(&mut stream).await;
+    })
+    .await;
+
+    (fake_server::factory(address), connections)
+}
+
+#[tokio::test]
+async fn unsent_accounting_failed_over() {
+    let (factory, connections) = accounting_server().await;
+    let client = FailoverClient::new(vec![
+        Client::new(unreachable_factory(), None::<&[u8]>),
+        Client::new(factory, None::<&[u8]>),
+    ])
+    .expect("server list should be non-empty");
+
+    // the start record never reached the first server, so it's safe to send it to the second one
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let (task, _) = client
+        .account_begin(context, &[])
+        .await
+        .expect("accounting start should have been failed over");
+
+    // the task sticks to the server that accepted its start record
+    assert!(std::ptr::eq(client.last_working(), &client.servers()[1]));
+    task.stop(&[])
+        .await
+        .expect("error stopping accounting task");
+
+    // the start & stop records were each sent on their own connection
+    assert_eq!(connections.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn no_sent_accounting_failover_by_default() {
+    let (dropping_factory, dropping_connections) = dropping_server().await;
+    let (factory, connections) = accounting_server().await;
+    let client = FailoverClient::new(vec![
+        Client::new(dropping_factory, None::<&[u8]>),
+        Client::new(factory, None::<&[u8]>),
+    ])
+    .expect("server list should be non-empty");
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .account_begin(context, &[])
+        .await
+        .err()
+        .expect("accounting start shouldn't have been failed over");
+
+    assert!(
+        matches!(error, tacacs_plus::ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(dropping_connections.load(Ordering::Relaxed), 1);
+    assert_eq!(connections.load(Ordering::Relaxed), 0);
+}
+
+#[tokio::test]
+async fn sent_accounting_failover_enabled() {
+    let (dropping_factory, _) = dropping_server().await;
+    let (factory, _) = accounting_server().await;
+    let client = FailoverClient::new(vec![
+        Client::new(dropping_factory, None::<&[u8]>),
+        Client::new(factory, None::<&[u8]>),
+    ])
+    .expect("server list should be non-empty")
+    .with_non_idempotent_failover(true);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let (task, _) = client
+        .account_begin(context, &[])
+        .await
+        .expect("accounting start should have been failed over");
+
+    assert!(std::ptr::eq(client.last_working(), &client.servers()[1]));
+    task.stop(&[])
+        .await
+        .expect("error stopping accounting task");
+}
+
+#[tokio::test]
+async fn malformed_reply_not_remembered() {
+    let (address, _) = fake_server::start(|mut stream| async move {
+        if let Some(request) = fake_server::read_packet(&mut stream).await {
+            // too short to be a valid accounting reply
+            fake_server::reply(&mut stream, &request, fake_server::UNENCRYPTED, &[0]).await;
+        }
+    })
+    .await;
+
+    let client = FailoverClient::new(vec![
+        Client::new(unreachable_factory(), None::<&[u8]>),
+        Client::new(fake_server::factory(address), None::<&[u8]>),
+    ])
+    .expect("server list should be non-empty");
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .account_begin(context, &[])
+        .await
+        .err()
+        .expect("malformed reply should have been rejected");
+
+    assert!(
+        matches!(error, tacacs_plus::ClientError::InvalidPacketReceived(_)),
+        "unexpected error: {error:?}"
+    );
+
+    // the second server didn't actually reply, so it isn't known to be working
+    assert!(std::ptr::eq(client.last_working(), &client.servers()[0]));
+}
+
+#[test]
+fn empty_server_list() {
+    assert!(FailoverClient::<Compat<TcpStream>>::new(Vec::new()).is_none());
+}
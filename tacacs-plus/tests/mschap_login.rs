@@ -0,0 +1,58 @@
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+
+use tacacs_plus::{AuthenticationType, Client, ConnectionFactory, ContextBuilder, ResponseStatus};
+
+mod common;
+
+// this test is ignored since the test servers aren't configured with MS-CHAP support
+// (shrubbery's tac_plus has to be built with --enable-mschap and a DES implementation)
+#[tokio::test]
+#[ignore]
+async fn mschap_success() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "something different", AuthenticationType::MsChap)
+        .await
+        .expect("error completing MS-CHAP authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+}
+
+// ignored for the same reason as the MS-CHAPv1 test
+#[tokio::test]
+#[ignore]
+async fn mschap_v2_success() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "something different", AuthenticationType::MsChapV2)
+        .await
+        .expect("error completing MS-CHAPv2 authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+}
@@ -0,0 +1,274 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt, TryFutureExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt};
+
+use tacacs_plus::{AuthenticationType, BackgroundTask, Client, ClientError, ConnectionFactory};
+use tacacs_plus::{ContextBuilder, ResponseStatus, Spawner, Timeouts};
+
+mod common;
+use common::TokioTimer;
+
+struct TokioSpawner;
+
+impl Spawner for TokioSpawner {
+    fn spawn(&self, task: BackgroundTask) {
+        tokio::spawn(task);
+    }
+}
+
+const SINGLE_CONNECTION: u8 = 0x04;
+const UNENCRYPTED: u8 = 0x01;
+
+/// Reads an unobfuscated PAP authentication start, returning its header and username.
+async fn read_start(stream: &mut Compat<TcpStream>) -> ([u8; 12], String) {
+    try_read_start(stream)
+        .await
+        .expect("couldn't read authentication start")
+}
+
+/// Reads an unobfuscated PAP authentication start, returning `None` if the connection was closed.
+async fn try_read_start(stream: &mut Compat<TcpStream>) -> Option<([u8; 12], String)> {
+    let mut header = [0; 12];
+    stream.read_exact(&mut header).await.ok()?;
+
+    let body_length = u32::from_be_bytes(header[8..12].try_into().unwrap());
+    let mut body = vec![0; body_length as usize];
+    stream.read_exact(&mut body).await.unwrap();
+
+    // the username directly follows the 8 bytes of fixed-length fields
+    let user_length = body[4] as usize;
+    let user = String::from_utf8(body[8..8 + user_length].to_vec()).unwrap();
+
+    Some((header, user))
+}
+
+/// Replies to an authentication start, passing users whose name starts with "pass" and failing everyone else.
+async fn reply(stream: &mut Compat<TcpStream>, header: [u8; 12], user: &str, flags: u8) {
+    let status = if user.starts_with("pass") { 0x01 } else { 0x02 };
+
+    let mut reply = Vec::new();
+    // same version & type, sequence number 2
+    reply.extend([header[0], header[1], 2, flags]);
+    // same session id
+    reply.extend(&header[4..8]);
+    reply.extend(6u32.to_be_bytes());
+    // status, no flags, empty server message & data
+    reply.extend([status, 0, 0, 0, 0, 0]);
+
+    stream.write_all(&reply).await.unwrap();
+}
+
+/// Starts a fake server that handles connections with the provided function, returning its address and a count of accepted connections.
+async fn start_server<F, Fut>(handler: F) -> (SocketAddr, Arc<AtomicUsize>)
+where
+    F: Fn(Compat<TcpStream>) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.expect("couldn't accept connection");
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(handler(stream.compat()));
+        }
+    });
+
+    (address, connections)
+}
+
+fn multiplexed_client(address: SocketAddr) -> Client<Compat<TcpStream>> {
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncReadCompatExt::compat)
+            .boxed()
+    });
+
+    Client::new(factory, None::<&[u8]>).with_multiplexing(TokioSpawner)
+}
+
+#[tokio::test]
+async fn replies_routed_by_session_id() {
+    const SESSIONS: usize = 4;
+
+    let (address, connections) = start_server(|mut stream| async move {
+        // the first session negotiates single connection mode on its own
+        let (header, user) = read_start(&mut stream).await;
+        reply(&mut stream, header, &user, UNENCRYPTED | SINGLE_CONNECTION).await;
+
+        // the rest are all in flight at once, and are replied to in reverse order
+        let mut requests = Vec::new();
+        for _ in 1..SESSIONS {
+            requests.push(read_start(&mut stream).await);
+        }
+
+        for (header, user) in requests.into_iter().rev() {
+            reply(&mut stream, header, &user, UNENCRYPTED).await;
+        }
+    })
+    .await;
+
+    let client = multiplexed_client(address);
+
+    let sessions = (0..SESSIONS).map(|index| {
+        let user = if index % 2 == 0 { "pass" } else { "fail" };
+        let context = ContextBuilder::new(format!("{user}-{index}")).build();
+        let client = &client;
+
+        async move {
+            let response = client
+                .authenticate(context, "hunter2", AuthenticationType::Pap)
+                .await
+                .expect("error completing authentication session");
+            (index, response.status)
+        }
+    });
+
+    // the sessions can only complete if they're all in flight on the same connection
+    let responses =
+        tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(sessions))
+            .await
+            .expect("sessions should have been multiplexed");
+
+    for (index, status) in responses {
+        let expected = if index % 2 == 0 {
+            ResponseStatus::Success
+        } else {
+            ResponseStatus::Failure
+        };
+        assert_eq!(status, expected, "session {index} got the wrong reply");
+    }
+
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn separate_connections_without_single_connection_mode() {
+    const SESSIONS: usize = 3;
+
+    let (address, connections) = start_server(|mut stream| async move {
+        let (header, user) = read_start(&mut stream).await;
+        reply(&mut stream, header, &user, UNENCRYPTED).await;
+    })
+    .await;
+
+    let client = multiplexed_client(address);
+
+    for index in 0..SESSIONS {
+        let context = ContextBuilder::new(format!("pass-{index}")).build();
+        let response = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.authenticate(context, "hunter2", AuthenticationType::Pap),
+        )
+        .await
+        .expect("session should have completed")
+        .expect("error completing authentication session");
+
+        assert_eq!(response.status, ResponseStatus::Success);
+    }
+
+    // the server didn't agree to single connection mode, so each session opened its own connection
+    assert_eq!(connections.load(Ordering::SeqCst), SESSIONS);
+}
+
+#[tokio::test]
+async fn idle_connection_replaced() {
+    let (address, connections) = start_server(|mut stream| async move {
+        while let Some((header, user)) = try_read_start(&mut stream).await {
+            reply(&mut stream, header, &user, UNENCRYPTED | SINGLE_CONNECTION).await;
+        }
+    })
+    .await;
+
+    let client = multiplexed_client(address).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            idle: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    let authenticate = || {
+        let context = ContextBuilder::new("pass".to_owned()).build();
+        client.authenticate(context, "hunter2", AuthenticationType::Pap)
+    };
+
+    let response = authenticate()
+        .await
+        .expect("error completing first session");
+    assert_eq!(response.status, ResponseStatus::Success);
+    let response = authenticate()
+        .await
+        .expect("error completing second session");
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // the shared connection isn't reused once it's been idle for too long
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let response = authenticate()
+        .await
+        .expect("error completing third session");
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn unresponsive_connection_replaced() {
+    let (address, connections) = start_server(|mut stream| async move {
+        // the server stops replying on a connection once a session from a "hang" user is started on it
+        let mut replying = true;
+
+        while let Some((header, user)) = try_read_start(&mut stream).await {
+            replying &= !user.starts_with("hang");
+
+            if replying {
+                reply(&mut stream, header, &user, UNENCRYPTED | SINGLE_CONNECTION).await;
+            }
+        }
+    })
+    .await;
+
+    let client = multiplexed_client(address).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            read: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    let authenticate = |user: &str| {
+        let context = ContextBuilder::new(user.to_owned()).build();
+        client.authenticate(context, "hunter2", AuthenticationType::Pap)
+    };
+
+    let response = authenticate("pass")
+        .await
+        .expect("error completing first session");
+    assert_eq!(response.status, ResponseStatus::Success);
+
+    let error = authenticate("hang")
+        .await
+        .expect_err("second session should have timed out");
+    assert!(
+        matches!(error, ClientError::Timeout),
+        "unexpected error: {error:?}"
+    );
+
+    // the connection that timed out isn't used for new sessions
+    let response = authenticate("pass")
+        .await
+        .expect("error completing third session");
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
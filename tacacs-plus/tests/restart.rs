@@ -0,0 +1,111 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tacacs_plus::{AuthenticationType, Client, ContextBuilder, ResponseStatus};
+
+mod common;
+use common::fake_server::{self, status};
+
+/// Starts a fake server that replies to each authentication start with the status & data returned by `respond`
+/// for its authentication type, returning its address and the authentication types of the received starts.
+async fn restart_server(respond: fn(u8) -> (u8, Vec<u8>)) -> (SocketAddr, Arc<Mutex<Vec<u8>>>) {
+    let received_types = Arc::new(Mutex::new(Vec::new()));
+
+    let types = received_types.clone();
+    let (address, _) = fake_server::start(move |mut stream| {
+        let types = types.clone();
+
+        async move {
+            while let Some(start) = fake_server::read_packet(&mut stream).await {
+                let (_, authentication_type, _, _) = fake_server::authentication_start(&start.body);
+                types.lock().unwrap().push(authentication_type);
+
+                let (status, data) = respond(authentication_type);
+                let reply = fake_server::authentication_reply(status, "", &data);
+                fake_server::reply(&mut stream, &start, fake_server::UNENCRYPTED, &reply).await;
+            }
+        }
+    })
+    .await;
+
+    (address, received_types)
+}
+
+/// Asks for a restart with CHAP or MS-CHAPv2 if PAP is used, and accepts anything else.
+fn restart_from_pap(authentication_type: u8) -> (u8, Vec<u8>) {
+    if authentication_type == 0x02 {
+        // NotSet, an unknown type, PAP, CHAP & MS-CHAPv2
+        (status::RESTART, vec![0x00, 0xff, 0x02, 0x03, 0x06])
+    } else {
+        (status::PASS, Vec::new())
+    }
+}
+
+#[tokio::test]
+async fn restart_with_first_untried_type() {
+    let (address, received_types) = restart_server(restart_from_pap).await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("someuser".to_owned())
+        .allow_restart(true)
+        .build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+
+    // PAP was already tried, so CHAP is the next type used
+    assert_eq!(*received_types.lock().unwrap(), [0x02, 0x03]);
+}
+
+#[tokio::test]
+async fn restart_reported_as_failure_if_not_allowed() {
+    let (address, received_types) = restart_server(restart_from_pap).await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Failure);
+    assert_eq!(
+        response.restart_types,
+        Some(vec![
+            AuthenticationType::Pap,
+            AuthenticationType::Chap,
+            AuthenticationType::MsChapV2
+        ])
+    );
+
+    // the session wasn't restarted
+    assert_eq!(*received_types.lock().unwrap(), [0x02]);
+}
+
+#[tokio::test]
+async fn restart_loop_ends() {
+    // the server always asks for a restart with one of PAP, CHAP, MS-CHAP or MS-CHAPv2
+    let (address, received_types) =
+        restart_server(|_| (status::RESTART, vec![0x02, 0x03, 0x05, 0x06])).await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("someuser".to_owned())
+        .allow_restart(true)
+        .build();
+    let response = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.authenticate(context, "hunter2", AuthenticationType::Pap),
+    )
+    .await
+    .expect("restart loop should have ended")
+    .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Failure);
+
+    // each type is only tried once
+    assert_eq!(*received_types.lock().unwrap(), [0x02, 0x03, 0x05, 0x06]);
+}
@@ -0,0 +1,214 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{FutureExt, TryFutureExt};
+use tokio::net::TcpStream;
+use tokio_util::compat::{Compat, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::{AuthenticationType, Client, ClientError, ConnectionFactory, ContextBuilder};
+use tacacs_plus::{Prompt, PromptFuture, PromptResponse, Prompter, ResponseStatus, RetryPolicy};
+
+mod common;
+use common::fake_server::{self, status};
+use common::TokioTimer;
+
+/// Returns a factory whose first `failures` connections fail, along with a counter of how many times it was called.
+fn flaky_factory(failures: usize) -> (ConnectionFactory<Compat<TcpStream>>, Arc<AtomicUsize>) {
+    let address = common::get_server_address();
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let counter = calls.clone();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        if counter.fetch_add(1, Ordering::Relaxed) < failures {
+            futures::future::err(io::Error::from(io::ErrorKind::ConnectionRefused)).boxed()
+        } else {
+            TcpStream::connect(address.clone())
+                .map_ok(TokioAsyncWriteCompatExt::compat_write)
+                .boxed()
+        }
+    });
+
+    (factory, calls)
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn retries_after_connection_failure() {
+    let (factory, calls) = flaky_factory(2);
+    let client =
+        Client::new(factory, Some(common::SECRET_KEY)).with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "authentication failed, full response: {response:?}"
+    );
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn no_retry_after_failure() {
+    let (factory, calls) = flaky_factory(0);
+    let client =
+        Client::new(factory, Some(common::SECRET_KEY)).with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "wrong password", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Failure);
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn attempts_exhausted() {
+    let (factory, calls) = flaky_factory(usize::MAX);
+    let client =
+        Client::new(factory, Some(common::SECRET_KEY)).with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect_err("authentication should have failed");
+
+    assert!(
+        matches!(error, ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+}
+
+#[tokio::test]
+async fn accounting_not_retried_by_default() {
+    let (factory, calls) = flaky_factory(usize::MAX);
+    let client =
+        Client::new(factory, Some(common::SECRET_KEY)).with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("account".to_owned()).build();
+    let error = client
+        .account_begin(context, Vec::new())
+        .await
+        .err()
+        .expect("accounting should have failed");
+
+    assert!(
+        matches!(error, ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+/// Starts a fake server that drops its first connection after reading an authentication start, and accepts
+/// any password on later ones (prompting for it in ASCII sessions).
+///
+/// The number of connections accepted by the server is returned along with its address.
+async fn dropping_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let dropped = Arc::new(AtomicBool::new(false));
+
+    fake_server::start(move |mut stream| {
+        let dropped = dropped.clone();
+
+        async move {
+            let start = fake_server::read_packet(&mut stream).await.unwrap();
+
+            // the first connection is closed mid-session, before the server replies
+            if !dropped.swap(true, Ordering::SeqCst) {
+                return;
+            }
+
+            let (_, authentication_type, _, _) = fake_server::authentication_start(&start.body);
+            let mut last_packet = start;
+
+            // ASCII sessions are prompted for a password
+            if authentication_type == 0x01 {
+                let reply = fake_server::authentication_reply(status::GETPASS, "Password: ", &[]);
+                fake_server::reply(&mut stream, &last_packet, fake_server::UNENCRYPTED, &reply)
+                    .await;
+                last_packet = fake_server::read_packet(&mut stream).await.unwrap();
+            }
+
+            let reply = fake_server::authentication_reply(status::PASS, "", &[]);
+            fake_server::reply(&mut stream, &last_packet, fake_server::UNENCRYPTED, &reply).await;
+        }
+    })
+    .await
+}
+
+/// A prompter that answers every prompt with the same password.
+struct PasswordAnswerer;
+
+impl Prompter for PasswordAnswerer {
+    fn prompt<'prompt>(&'prompt mut self, _prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        Box::pin(async { PromptResponse::Answer("hunter2".to_owned()) })
+    }
+}
+
+#[tokio::test]
+async fn retries_after_connection_dropped() {
+    let (address, connections) = dropping_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>)
+        .with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn ascii_retried_with_known_password() {
+    let (address, connections) = dropping_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>)
+        .with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Ascii)
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn interactive_not_retried() {
+    let (address, connections) = dropping_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>)
+        .with_retry_policy(TokioTimer, policy());
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate_interactive(context, &mut PasswordAnswerer)
+        .await
+        .expect_err("interactive session shouldn't have been retried");
+
+    assert!(
+        matches!(error, ClientError::IOError(_)),
+        "unexpected error: {error:?}"
+    );
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,206 @@
+use futures::{FutureExt, TryFutureExt};
+use md5::{Digest, Md5};
+use tokio::net::TcpStream;
+use tokio_util::compat::TokioAsyncWriteCompatExt;
+
+use std::net::SocketAddr;
+
+use tacacs_plus::{Client, ClientError, ConnectionFactory, ContextBuilder, ResponseStatus};
+use tacacs_plus::{OutboundAuthentication, OutboundCredentials};
+
+mod common;
+use common::fake_server::{self, status};
+
+/// The outbound secret the fake server calculates CHAP responses with.
+const OUTBOUND_SECRET: &[u8] = b"outbound secret";
+
+/// Calculates a CHAP response as specified in RFC1994 section 4.1.
+fn chap_response(identifier: u8, secret: &[u8], challenge: &[u8]) -> [u8; 16] {
+    let mut hasher = Md5::new();
+    hasher.update([identifier]);
+    hasher.update(secret);
+    hasher.update(challenge);
+    hasher.finalize().into()
+}
+
+/// Starts a fake server that answers outbound authentication requests, sending a truncated CHAP response to the user "truncated".
+async fn outbound_server() -> SocketAddr {
+    let (address, _) = fake_server::start(|mut stream| async move {
+        let start = fake_server::read_packet(&mut stream).await.unwrap();
+        let (action, authentication_type, user, data) =
+            fake_server::authentication_start(&start.body);
+
+        // SENDAUTH action
+        assert_eq!(action, 0x04);
+
+        let credentials = match authentication_type {
+            // PAP
+            0x02 => b"outbound pass".to_vec(),
+
+            // CHAP, where the data is the identifier followed by the challenge
+            0x03 => {
+                let response = chap_response(data[0], OUTBOUND_SECRET, &data[1..]);
+                if user == "truncated" {
+                    response[..15].to_vec()
+                } else {
+                    response.to_vec()
+                }
+            }
+
+            other => panic!("unexpected authentication type {other}"),
+        };
+
+        let reply = fake_server::authentication_reply(status::PASS, "", &credentials);
+        fake_server::reply(&mut stream, &start, fake_server::UNENCRYPTED, &reply).await;
+    })
+    .await;
+
+    address
+}
+
+#[tokio::test]
+async fn sendauth_pap_credentials() {
+    let address = outbound_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .send_auth(context, OutboundAuthentication::Pap)
+        .await
+        .expect("error completing outbound PAP session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(
+        response.outbound_credentials,
+        Some(OutboundCredentials::Password(b"outbound pass".to_vec()))
+    );
+}
+
+#[tokio::test]
+async fn sendauth_chap_credentials() {
+    let address = outbound_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let identifier = 42;
+    let challenge = b"challenge from the remote peer";
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .send_auth(
+            context,
+            OutboundAuthentication::Chap {
+                identifier,
+                challenge,
+            },
+        )
+        .await
+        .expect("error completing outbound CHAP session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(
+        response.outbound_credentials,
+        Some(OutboundCredentials::ChapResponse(chap_response(
+            identifier,
+            OUTBOUND_SECRET,
+            challenge
+        )))
+    );
+}
+
+#[tokio::test]
+async fn sendauth_chap_wrong_length() {
+    let address = outbound_server().await;
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>);
+
+    let context = ContextBuilder::new("truncated".to_owned()).build();
+    let error = client
+        .send_auth(
+            context,
+            OutboundAuthentication::Chap {
+                identifier: 1,
+                challenge: b"challenge",
+            },
+        )
+        .await
+        .expect_err("a CHAP response of the wrong length should be rejected");
+
+    match error {
+        ClientError::ProtocolError { data, .. } => assert_eq!(data.len(), 15),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+// these tests are ignored since TACACS+ NG doesn't support outbound authentication
+// (and shrubbery's tac_plus only does so for users with outbound secrets configured)
+#[tokio::test]
+#[ignore]
+async fn sendauth_pap() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .send_auth(context, OutboundAuthentication::Pap)
+        .await
+        .expect("error completing outbound PAP session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "outbound authentication failed, full response: {response:?}"
+    );
+    assert_eq!(
+        response.outbound_credentials,
+        Some(OutboundCredentials::Password(b"outbound pass".to_vec()))
+    );
+}
+
+#[tokio::test]
+#[ignore]
+async fn sendauth_chap() {
+    let address = common::get_server_address();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address.clone())
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+    let client = Client::new(factory, Some(common::SECRET_KEY));
+
+    // values as they would be received from the PPP peer
+    let identifier = 7;
+    let challenge = b"challenge from the remote peer";
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .send_auth(
+            context,
+            OutboundAuthentication::Chap {
+                identifier,
+                challenge,
+            },
+        )
+        .await
+        .expect("error completing outbound CHAP session");
+
+    assert_eq!(
+        response.status,
+        ResponseStatus::Success,
+        "outbound authentication failed, full response: {response:?}"
+    );
+
+    let mut hasher = Md5::new();
+    hasher.update([identifier]);
+    hasher.update(b"something different");
+    hasher.update(challenge);
+    let expected: [u8; 16] = hasher.finalize().into();
+
+    assert_eq!(
+        response.outbound_credentials,
+        Some(OutboundCredentials::ChapResponse(expected))
+    );
+}
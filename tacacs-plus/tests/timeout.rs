@@ -0,0 +1,296 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::channel::mpsc;
+use futures::{AsyncReadExt, AsyncWriteExt, FutureExt, StreamExt, TryFutureExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+use tacacs_plus::{AuthenticationType, Client, ClientError, ConnectionFactory, ContextBuilder};
+use tacacs_plus::{Prompt, PromptFuture, PromptResponse, Prompter, ResponseStatus, Timeouts};
+
+mod common;
+use common::fake_server::{self, status};
+use common::TokioTimer;
+
+#[tokio::test]
+async fn read_timeout() {
+    // a server that accepts connections but never replies
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        loop {
+            let (stream, _) = listener.accept().await.expect("couldn't accept connection");
+            connections.push(stream);
+        }
+    });
+
+    let opened = Arc::new(AtomicUsize::new(0));
+    let counter = opened.clone();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+
+    let client = Client::new(factory, Some("key")).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            read: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    for _ in 0..2 {
+        let context = ContextBuilder::new("someuser".to_owned()).build();
+        let error = client
+            .authenticate(context, "hunter2", AuthenticationType::Pap)
+            .await
+            .expect_err("authentication should have timed out");
+        assert!(
+            matches!(error, ClientError::Timeout),
+            "unexpected error: {error:?}"
+        );
+    }
+
+    // the timed out connection should have been dropped rather than reused
+    assert_eq!(opened.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn connect_timeout() {
+    // a factory whose connections never finish opening
+    let factory: ConnectionFactory<Compat<TcpStream>> =
+        Box::new(|| futures::future::pending().boxed());
+
+    let client = Client::new(factory, Some("key")).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            connect: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect_err("authentication should have timed out");
+    assert!(
+        matches!(error, ClientError::Timeout),
+        "unexpected error: {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn session_timeout() {
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (_stream, _) = listener.accept().await.expect("couldn't accept connection");
+        futures::future::pending::<()>().await;
+    });
+
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+
+    let client = Client::new(factory, Some("key")).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            session: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let error = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect_err("authentication should have timed out");
+    assert!(
+        matches!(error, ClientError::Timeout),
+        "unexpected error: {error:?}"
+    );
+}
+
+/// Passes every unobfuscated authentication start on a connection with the single connection flag set,
+/// reporting on the provided channel once the client closes the connection.
+async fn single_connection_server(stream: TcpStream, closed: mpsc::UnboundedSender<()>) {
+    let mut stream = stream.compat();
+
+    loop {
+        let mut header = [0; 12];
+        if stream.read_exact(&mut header).await.is_err() {
+            break;
+        }
+
+        let body_length = u32::from_be_bytes(header[8..12].try_into().unwrap());
+        let mut body = vec![0; body_length as usize];
+        stream.read_exact(&mut body).await.unwrap();
+
+        let mut reply = Vec::new();
+        // same version & type, sequence number 2, unencrypted & single connection flags set
+        reply.extend([header[0], header[1], 2, 0x05]);
+        // same session id
+        reply.extend(&header[4..8]);
+        reply.extend(6u32.to_be_bytes());
+        // PASS status, no flags, empty server message & data
+        reply.extend([0x01, 0, 0, 0, 0, 0]);
+
+        stream.write_all(&reply).await.unwrap();
+    }
+
+    let _ = closed.unbounded_send(());
+}
+
+/// Starts a server that keeps connections open in single connection mode, returning a factory that counts
+/// the connections it opens along with a channel that's notified as the client closes each one.
+async fn persistent_server() -> (
+    ConnectionFactory<Compat<TcpStream>>,
+    Arc<AtomicUsize>,
+    mpsc::UnboundedReceiver<()>,
+) {
+    let listener = TcpListener::bind("localhost:0")
+        .await
+        .expect("couldn't bind listener");
+    let address = listener.local_addr().unwrap();
+    let (closed_sender, closed) = mpsc::unbounded();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.expect("couldn't accept connection");
+            tokio::spawn(single_connection_server(stream, closed_sender.clone()));
+        }
+    });
+
+    let opened = Arc::new(AtomicUsize::new(0));
+    let counter = opened.clone();
+    let factory: ConnectionFactory<_> = Box::new(move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+        TcpStream::connect(address)
+            .map_ok(TokioAsyncWriteCompatExt::compat_write)
+            .boxed()
+    });
+
+    (factory, opened, closed)
+}
+
+async fn authenticate(client: &Client<Compat<TcpStream>>) {
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate(context, "hunter2", AuthenticationType::Pap)
+        .await
+        .expect("error completing authentication session");
+    assert_eq!(response.status, ResponseStatus::Success);
+}
+
+#[tokio::test]
+async fn idle_timeout() {
+    let (factory, opened, mut closed) = persistent_server().await;
+
+    let client = Client::new(factory, None::<&[u8]>).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            idle: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+
+    // the connection is reused while it's still in use
+    authenticate(&client).await;
+    authenticate(&client).await;
+    assert_eq!(opened.load(Ordering::Relaxed), 1);
+
+    // but closed in favor of a new one once it's been idle for too long
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    authenticate(&client).await;
+    assert_eq!(opened.load(Ordering::Relaxed), 2);
+
+    tokio::time::timeout(Duration::from_secs(1), closed.next())
+        .await
+        .expect("idle connection should have been closed");
+}
+
+#[tokio::test]
+async fn liveness_check_closes_idle_connections() {
+    let (factory, _, mut closed) = persistent_server().await;
+
+    let client = Client::new(factory, None::<&[u8]>).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            idle: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    );
+    tokio::spawn(client.liveness_check(TokioTimer, Duration::from_millis(50)));
+
+    authenticate(&client).await;
+
+    // the connection should be closed in the background, without another session being started
+    tokio::time::timeout(Duration::from_secs(2), closed.next())
+        .await
+        .expect("idle connection should have been closed in the background");
+}
+
+/// A prompter that takes a while to answer with a password, like a user typing it in.
+struct SlowPrompter(Duration);
+
+impl Prompter for SlowPrompter {
+    fn prompt<'prompt>(&'prompt mut self, _prompt: Prompt<'prompt>) -> PromptFuture<'prompt> {
+        Box::pin(async move {
+            tokio::time::sleep(self.0).await;
+            PromptResponse::Answer("hunter2".to_owned())
+        })
+    }
+}
+
+#[tokio::test]
+async fn idle_timeout_ignored_mid_session() {
+    let (address, connections) = fake_server::start(|mut stream| async move {
+        let start = fake_server::read_packet(&mut stream).await.unwrap();
+
+        // a continue sent on a new connection can't be part of any session
+        if start.sequence_number() != 1 {
+            return;
+        }
+
+        let reply = fake_server::authentication_reply(status::GETPASS, "Password: ", &[]);
+        fake_server::reply(&mut stream, &start, fake_server::UNENCRYPTED, &reply).await;
+
+        if let Some(answer) = fake_server::read_packet(&mut stream).await {
+            let reply = fake_server::authentication_reply(status::PASS, "", &[]);
+            fake_server::reply(&mut stream, &answer, fake_server::UNENCRYPTED, &reply).await;
+        }
+    })
+    .await;
+
+    let client = Client::new(fake_server::factory(address), None::<&[u8]>).with_timeouts(
+        TokioTimer,
+        Timeouts {
+            idle: Some(Duration::from_millis(50)),
+            ..Default::default()
+        },
+    );
+
+    // the prompter takes longer than the idle timeout to answer
+    let context = ContextBuilder::new("someuser".to_owned()).build();
+    let response = client
+        .authenticate_interactive(context, &mut SlowPrompter(Duration::from_millis(200)))
+        .await
+        .expect("error completing authentication session");
+
+    assert_eq!(response.status, ResponseStatus::Success);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}